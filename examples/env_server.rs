@@ -0,0 +1,100 @@
+// 強化学習用の環境を標準入出力で操作する(python/invader_env.pyから使う)
+// 実行方法: cargo run --release --example env_server -- [--obs dotmap|rgba:<縮小率>]
+//     [--frame-skip <tick数>] [--sticky <確率>] [--noop-max <tick数>] [--life-penalty <値>]
+//
+// 1行ずつコマンドを受け取る
+//     reset <シード>
+//     step <行動の番号(Action::ALLでの位置)>
+// 応答として次の1行に続けて、観測のバイト列をそのまま書き出す
+//     <観測のバイト数> <報酬> <終了したら1> <得点> <残機> <ステージ数> <tick数> <打ち切りなら1>
+// 不正なコマンドには観測を付けずに次の1行だけを返す
+//     error <理由>
+use invader_in_browser::env::{Action, Env, EnvConfig, ObsKind};
+use std::io::{self, BufRead, Write};
+
+fn main() {
+    let config = parse_args();
+    let mut env = Env::new(config);
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in stdin.lock().lines() {
+        let line = line.unwrap();
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let arg: Option<u64> = words.next().and_then(|word| word.parse().ok());
+        let (obs, reward, done, info) = match (command, arg) {
+            ("reset", Some(seed)) => {
+                let (obs, info) = env.reset(seed);
+                (obs, 0., false, info)
+            }
+            ("step", Some(i)) => match Action::from_index(i as usize) {
+                Some(action) => env.step(action),
+                None => {
+                    write_error(&mut out, &format!("不正な行動です: {}", i));
+                    continue;
+                }
+            },
+            ("quit", _) => break,
+            _ => {
+                write_error(&mut out, &format!("不正なコマンドです: {}", line));
+                continue;
+            }
+        };
+        writeln!(
+            out,
+            "{} {} {} {} {} {} {} {}",
+            obs.len(),
+            reward,
+            done as u8,
+            info.score,
+            info.life,
+            info.stage,
+            info.ticks,
+            info.truncated as u8
+        )
+        .unwrap();
+        out.write_all(&obs).unwrap();
+        out.flush().unwrap();
+    }
+}
+
+// クライアントが応答を待ち続けないように、エラーも1行で返す
+fn write_error(out: &mut impl Write, message: &str) {
+    eprintln!("{}", message);
+    writeln!(out, "error {}", message).unwrap();
+    out.flush().unwrap();
+}
+
+// コマンドライン引数から環境の設定を作る(不正な値は無視して既定値を使う)
+fn parse_args() -> EnvConfig {
+    let mut config = EnvConfig::default();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for pair in args.chunks(2) {
+        let value = match pair.get(1) {
+            Some(value) => value.as_str(),
+            None => break,
+        };
+        match pair[0].as_str() {
+            "--obs" => {
+                if value == "dotmap" {
+                    config.obs = ObsKind::DotMap;
+                } else if let Some(scale) = value.strip_prefix("rgba:") {
+                    if let Ok(scale @ 1..) = scale.parse() {
+                        config.obs = ObsKind::Rgba { scale };
+                    }
+                }
+            }
+            "--frame-skip" => {
+                if let Ok(frame_skip @ 1..) = value.parse() {
+                    config.frame_skip = frame_skip;
+                }
+            }
+            "--sticky" => config.sticky_prob = value.parse().unwrap_or(config.sticky_prob),
+            "--noop-max" => config.noop_max = value.parse().unwrap_or(config.noop_max),
+            "--life-penalty" => config.life_penalty = value.parse().unwrap_or(config.life_penalty),
+            _ => eprintln!("不明な引数です: {}", pair[0]),
+        }
+    }
+    config
+}
@@ -0,0 +1,42 @@
+// 強化学習用の環境で、行動をランダムに選ぶエージェントを何エピソードか動かす
+// 実行方法: cargo run --release --example random_agent -- <エピソード数>
+use invader_in_browser::env::{Action, Env, EnvConfig, ObsKind};
+use invader_in_browser::math::Rng;
+
+fn main() {
+    let episode_num: u64 = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(10);
+
+    let config = EnvConfig {
+        obs: ObsKind::Rgba { scale: 2 },
+        ..EnvConfig::default()
+    };
+    println!("observation shape: {:?}", config.obs.shape());
+    let mut env = Env::new(config);
+    let mut rng = Rng::new(12345);
+    for seed in 0..episode_num {
+        env.reset(seed);
+        let mut episode_return = 0.;
+        let mut steps = 0;
+        loop {
+            let action = Action::ALL[(rng.next_u64() % Action::ALL.len() as u64) as usize];
+            let (_obs, reward, done, info) = env.step(action);
+            episode_return += reward;
+            steps += 1;
+            if done {
+                println!(
+                    "episode {}: return {} / steps {} / score {} / stage {}{}",
+                    seed,
+                    episode_return,
+                    steps,
+                    info.score,
+                    info.stage,
+                    if info.truncated { " (truncated)" } else { "" }
+                );
+                break;
+            }
+        }
+    }
+}
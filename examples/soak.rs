@@ -0,0 +1,92 @@
+// 自動操縦で何ゲームも続けて遊び、パニックせずに最後まで遊べるかを調べる
+// 実行方法: cargo run --release --example soak -- <ゲーム数>
+use invader_in_browser::audio::Audio;
+use invader_in_browser::bot::Bot;
+use invader_in_browser::config::GameConfig;
+use invader_in_browser::game::{Game, PlayResult};
+use invader_in_browser::input::Actions;
+use std::panic::{self, AssertUnwindSafe};
+
+// 1ゲームの最大tick数(60tick/秒で1時間)
+const MAX_TICKS: u64 = 60 * 60 * 60;
+
+// 1ゲームの結果
+struct Record {
+    score: i32,
+    stage: usize,
+    ticks: u64,
+}
+
+fn main() {
+    let game_num: u64 = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(1000);
+
+    let mut records = Vec::new();
+    let mut crashed = Vec::new();
+    for seed in 0..game_num {
+        match panic::catch_unwind(AssertUnwindSafe(|| play(seed))) {
+            Ok(record) => records.push(record),
+            Err(_) => crashed.push(seed),
+        }
+    }
+
+    println!("games:   {}", game_num);
+    if !records.is_empty() {
+        let scores: Vec<i32> = records.iter().map(|r| r.score).collect();
+        let stages: Vec<usize> = records.iter().map(|r| r.stage).collect();
+        let ticks: u64 = records.iter().map(|r| r.ticks).sum();
+        println!(
+            "score:   min {} / avg {} / max {}",
+            scores.iter().min().unwrap(),
+            scores.iter().sum::<i32>() / scores.len() as i32,
+            scores.iter().max().unwrap()
+        );
+        println!(
+            "stage:   min {} / max {}",
+            stages.iter().min().unwrap(),
+            stages.iter().max().unwrap()
+        );
+        println!(
+            "ticks:   {} (timeout {})",
+            ticks,
+            records.iter().filter(|r| MAX_TICKS <= r.ticks).count()
+        );
+    }
+    println!("crashes: {}", crashed.len());
+    for seed in crashed.iter() {
+        println!("  seed {}", seed);
+    }
+    if !crashed.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+// シードを変えて1ゲーム遊ぶ(ゲームオーバーになるかMAX_TICKSで終える)
+fn play(seed: u64) -> Record {
+    let audio = Audio::silent();
+    let mut game = Game::new(GameConfig::default());
+    let mut bot = Bot::new(seed);
+    let mut actions = Actions::default();
+    game.start(1);
+    game.reset_stage(None, None);
+    let mut ticks = 0;
+    while ticks < MAX_TICKS {
+        ticks += 1;
+        actions.sample(&bot.think(&game));
+        match game.update(&actions, &audio) {
+            PlayResult::StageCleared => {
+                game.next_stage();
+                game.reset_stage(None, None);
+            }
+            PlayResult::GameOver => break,
+            _ => (),
+        }
+    }
+    Record {
+        score: game.player_bullet.score,
+        stage: game.stage,
+        ticks,
+    }
+}
@@ -0,0 +1,68 @@
+# 強化学習用の環境(examples/env_server.rs)をPythonから使うためのクライアント
+# 使い方:
+#     env = InvaderEnv(obs="rgba:2", frame_skip=4)
+#     obs = env.reset(seed=0)
+#     obs, reward, done, info = env.step(3)
+# 観測はbytesで返す(numpyがあればnp.frombuffer(obs, np.uint8).reshape(env.shape)で配列にできる)
+import os
+import subprocess
+
+# Action::ALLと同じ順番
+ACTIONS = ["NOOP", "LEFT", "RIGHT", "FIRE", "LEFTFIRE", "RIGHTFIRE"]
+
+REPO_DIR = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+
+
+class InvaderEnv:
+    def __init__(self, obs="dotmap", frame_skip=4, sticky=0.25, noop_max=30, life_penalty=100.0):
+        args = [
+            "cargo", "run", "--quiet", "--release", "--example", "env_server", "--",
+            "--obs", obs,
+            "--frame-skip", str(frame_skip),
+            "--sticky", str(sticky),
+            "--noop-max", str(noop_max),
+            "--life-penalty", str(life_penalty),
+        ]
+        self.proc = subprocess.Popen(args, cwd=REPO_DIR, stdin=subprocess.PIPE, stdout=subprocess.PIPE)
+        # 観測の(高さ, 幅, チャンネル数)
+        if obs == "dotmap":
+            self.shape = (26, 224, 1)
+        else:
+            scale = int(obs.split(":")[1])
+            self.shape = (256 // scale, 224 // scale, 4)
+        self.action_num = len(ACTIONS)
+
+    def reset(self, seed=0):
+        obs, _, _, _ = self._send("reset {}".format(seed))
+        return obs
+
+    def step(self, action):
+        if not 0 <= int(action) < self.action_num:
+            raise ValueError("action must be in 0..{}: {}".format(self.action_num - 1, action))
+        return self._send("step {}".format(int(action)))
+
+    def close(self):
+        if self.proc.poll() is None:
+            self.proc.stdin.write(b"quit\n")
+            self.proc.stdin.flush()
+            self.proc.wait()
+
+    def _send(self, command):
+        self.proc.stdin.write((command + "\n").encode())
+        self.proc.stdin.flush()
+        line = self.proc.stdout.readline()
+        if not line:
+            raise RuntimeError("env_server exited")
+        # 不正なコマンドには観測を付けずにエラーの1行だけが返る
+        if line.startswith(b"error "):
+            raise RuntimeError(line[len(b"error "):].decode().strip())
+        words = line.split()
+        obs = self.proc.stdout.read(int(words[0]))
+        info = {
+            "score": int(words[3]),
+            "life": int(words[4]),
+            "stage": int(words[5]),
+            "ticks": int(words[6]),
+            "truncated": words[7] == b"1",
+        }
+        return obs, float(words[1]), words[2] == b"1", info
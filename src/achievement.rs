@@ -0,0 +1,276 @@
+use crate::dot_map::DotMap;
+use crate::game::Game;
+use crate::player::ShotResult;
+use crate::stats::draw_lines;
+use crate::storage::Storage;
+use std::collections::VecDeque;
+
+const STORAGE_KEY: &str = "invader-achievements";
+// 解除を知らせる表示を出しておくtick数
+const TOAST_CNT: i32 = 180;
+
+// 実績
+#[derive(Clone, Copy, PartialEq)]
+pub enum Achievement {
+    // UFOを撃って300点を得る
+    UfoMax,
+    // 残機を減らさずにステージをクリアする
+    NoMiss,
+    // 最後の1匹を最高速度(右へ移動中)で倒す
+    TopSpeed,
+    // ステージ9まで到達する
+    Stage9,
+    // 1ゲームで10000点を得る
+    Score10000,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::UfoMax,
+        Achievement::NoMiss,
+        Achievement::TopSpeed,
+        Achievement::Stage9,
+        Achievement::Score10000,
+    ];
+
+    // 保存に使う名前
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::UfoMax => "ufo_max",
+            Achievement::NoMiss => "no_miss",
+            Achievement::TopSpeed => "top_speed",
+            Achievement::Stage9 => "stage_9",
+            Achievement::Score10000 => "score_10000",
+        }
+    }
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::UfoMax => "UFO 300",
+            Achievement::NoMiss => "NO MISS",
+            Achievement::TopSpeed => "TOP SPEED",
+            Achievement::Stage9 => "STAGE 9",
+            Achievement::Score10000 => "10000 POINTS",
+        }
+    }
+    // 解除の条件(ギャラリー画面に表示する)
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::UfoMax => "HIT THE UFO FOR 300",
+            Achievement::NoMiss => "CLEAR STAGE WITHOUT DYING",
+            Achievement::TopSpeed => "KILL LAST ONE AT TOP SPEED",
+            Achievement::Stage9 => "SURVIVE TO STAGE 9",
+            Achievement::Score10000 => "SCORE 10000 IN ONE GAME",
+        }
+    }
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|a| *a == self).unwrap()
+    }
+}
+
+// 解除済みの実績と、解除を知らせる表示
+pub struct Achievements {
+    unlocked: [bool; Achievement::ALL.len()],
+    // 表示待ちの解除された実績
+    toasts: VecDeque<Achievement>,
+    // 先頭の表示を出している残りtick数
+    toast_cnt: i32,
+}
+
+impl Achievements {
+    // 保存先から読み込む(保存されていなければすべて未解除)
+    // 1行に1つ、解除済みの実績の名前が保存されている
+    pub fn load(storage: &dyn Storage) -> Self {
+        let mut achievements = Achievements {
+            unlocked: [false; Achievement::ALL.len()],
+            toasts: VecDeque::new(),
+            toast_cnt: 0,
+        };
+        if let Some(text) = storage.load(STORAGE_KEY) {
+            for line in text.lines() {
+                match Achievement::ALL.iter().find(|a| a.key() == line.trim()) {
+                    Some(achievement) => achievements.unlocked[achievement.index()] = true,
+                    None => log::info!("実績の{}は不正なので読み飛ばします。", line),
+                }
+            }
+        }
+        achievements
+    }
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let text: Vec<&str> = Achievement::ALL
+            .iter()
+            .filter(|a| self.is_unlocked(**a))
+            .map(|a| a.key())
+            .collect();
+        storage.save(STORAGE_KEY, &text.join("\n"));
+    }
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked[achievement.index()]
+    }
+    // ゲームを1tick進めた後に呼び出し、新たに解除した実績があれば真を返す
+    // 練習モードとレベルエディタの試遊(ハイスコア表に載らないルール)では解除しない
+    pub fn check(&mut self, game: &Game) -> bool {
+        if game.mode.kind().is_none() {
+            return false;
+        }
+        let mut unlocked = false;
+        for achievement in Achievement::ALL {
+            if !self.is_unlocked(achievement) && Self::achieved(achievement, game) {
+                self.unlocked[achievement.index()] = true;
+                self.toasts.push_back(achievement);
+                unlocked = true;
+            }
+        }
+        unlocked
+    }
+    // 解除を知らせる表示の文字列(表示するものがなければNone)
+    pub fn toast(&mut self) -> Option<String> {
+        let achievement = *self.toasts.front()?;
+        self.toast_cnt += 1;
+        if TOAST_CNT < self.toast_cnt {
+            self.toasts.pop_front();
+            self.toast_cnt = 0;
+        }
+        Some(format!("UNLOCKED: {}", achievement.title()))
+    }
+    // ギャラリー画面を描く
+    pub fn draw(&self, dot_map: &mut DotMap, char_list: &[Vec<u8>]) {
+        let unlocked_num = self.unlocked.iter().filter(|u| **u).count();
+        let mut lines = vec![format!(
+            "UNLOCKED {}/{}",
+            unlocked_num,
+            Achievement::ALL.len()
+        )];
+        for achievement in Achievement::ALL {
+            let mark = if self.is_unlocked(achievement) {
+                '*'
+            } else {
+                '-'
+            };
+            lines.push(format!("{} {}", mark, achievement.title()));
+            lines.push(format!("  {}", achievement.description()));
+        }
+        draw_lines(dot_map, char_list, "ACHIEVEMENTS", &lines);
+    }
+    fn achieved(achievement: Achievement, game: &Game) -> bool {
+        match achievement {
+            Achievement::UfoMax => matches!(
+                game.player_bullet.shot_result,
+                Some(ShotResult::Ufo { score: 300 })
+            ),
+            Achievement::NoMiss => {
+                game.wave_cleared && matches!(game.history.last(), Some(s) if s.lives_lost == 0)
+            }
+            Achievement::TopSpeed => game.top_speed_kill,
+            Achievement::Stage9 => 9 <= game.stage,
+            Achievement::Score10000 => 10000 <= game.player_bullet.score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::game_mode::GameMode;
+    use crate::practice::Practice;
+    use crate::stats::StageStats;
+    use crate::storage::MemoryStorage;
+
+    fn empty() -> Achievements {
+        Achievements::load(&MemoryStorage::default())
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut storage = MemoryStorage::default();
+        let mut achievements = empty();
+        assert!(Achievement::ALL
+            .iter()
+            .all(|a| !achievements.is_unlocked(*a)));
+        achievements.unlocked[Achievement::NoMiss.index()] = true;
+        achievements.unlocked[Achievement::Stage9.index()] = true;
+        achievements.save(&mut storage);
+
+        let loaded = Achievements::load(&storage);
+        for achievement in Achievement::ALL {
+            let expected = matches!(achievement, Achievement::NoMiss | Achievement::Stage9);
+            assert_eq!(loaded.is_unlocked(achievement), expected);
+        }
+    }
+
+    #[test]
+    fn load_skips_unknown_names() {
+        let mut storage = MemoryStorage::default();
+        storage.save(STORAGE_KEY, "ufo_max\nunknown\n top_speed \n");
+        let loaded = Achievements::load(&storage);
+        assert!(loaded.is_unlocked(Achievement::UfoMax));
+        assert!(loaded.is_unlocked(Achievement::TopSpeed));
+        assert!(!loaded.is_unlocked(Achievement::NoMiss));
+    }
+
+    #[test]
+    fn ufo_max_needs_300_points() {
+        let mut game = Game::new(GameConfig::default());
+        game.player_bullet.shot_result = Some(ShotResult::Ufo { score: 150 });
+        assert!(!Achievements::achieved(Achievement::UfoMax, &game));
+        game.player_bullet.shot_result = Some(ShotResult::Ufo { score: 300 });
+        assert!(Achievements::achieved(Achievement::UfoMax, &game));
+    }
+
+    #[test]
+    fn no_miss_needs_cleared_wave_without_lost_lives() {
+        let mut game = Game::new(GameConfig::default());
+        game.history.push(StageStats::default());
+        assert!(!Achievements::achieved(Achievement::NoMiss, &game));
+        game.wave_cleared = true;
+        assert!(Achievements::achieved(Achievement::NoMiss, &game));
+        game.history.last_mut().unwrap().lives_lost = 1;
+        assert!(!Achievements::achieved(Achievement::NoMiss, &game));
+    }
+
+    #[test]
+    fn top_speed_follows_kill_flag() {
+        let mut game = Game::new(GameConfig::default());
+        assert!(!Achievements::achieved(Achievement::TopSpeed, &game));
+        game.top_speed_kill = true;
+        assert!(Achievements::achieved(Achievement::TopSpeed, &game));
+    }
+
+    #[test]
+    fn stage_and_score_thresholds() {
+        let mut game = Game::new(GameConfig::default());
+        game.stage = 8;
+        game.player_bullet.score = 9990;
+        assert!(!Achievements::achieved(Achievement::Stage9, &game));
+        assert!(!Achievements::achieved(Achievement::Score10000, &game));
+        game.stage = 9;
+        game.player_bullet.score = 10000;
+        assert!(Achievements::achieved(Achievement::Stage9, &game));
+        assert!(Achievements::achieved(Achievement::Score10000, &game));
+    }
+
+    #[test]
+    fn check_unlocks_once_and_queues_toast() {
+        let mut game = Game::new(GameConfig::default());
+        game.stage = 9;
+        let mut achievements = empty();
+        assert!(achievements.check(&game));
+        assert!(!achievements.check(&game));
+        assert_eq!(achievements.toast().as_deref(), Some("UNLOCKED: STAGE 9"));
+    }
+
+    #[test]
+    fn practice_and_editor_play_do_not_unlock() {
+        let mut game = Game::new(GameConfig::default());
+        game.stage = 9;
+        let mut achievements = empty();
+        game.mode = GameMode::Practice(Practice::default());
+        assert!(!achievements.check(&game));
+        game.mode = GameMode::Custom {
+            level: game.classic_level.clone(),
+            cleared: false,
+        };
+        assert!(!achievements.check(&game));
+        assert!(!achievements.is_unlocked(Achievement::Stage9));
+    }
+}
@@ -2,8 +2,12 @@ use crate::array_sprite::ArraySprite;
 use crate::audio::Audio;
 use crate::canvas;
 use crate::dot_map::DotMap;
+use crate::level;
 use crate::math::Vec2;
-use crate::player::Player;
+use crate::player::{self, Player};
+use crate::progression::StageParams;
+use crate::ruleset::{self, Ruleset};
+use crate::shield;
 
 enum BulletType {
     Squiggly, // ジグザグ型
@@ -11,12 +15,12 @@ enum BulletType {
     Rolling,  // ねじ型
 }
 
-struct Bullet {
-    pos: Vec2,
+pub struct Bullet {
+    pub pos: Vec2,
     btype: BulletType,
-    live: bool,      // 弾が画面上にある場合は真
+    pub live: bool,  // 弾が画面上にある場合は真
     flying_cnt: i32, // 弾が発射されてからの経過カウント
-    speed: i32,      // 移動速度(移動量)
+    pub speed: i32,  // 移動速度(移動量)
     // 爆発エフェクトの残り表示カウント
     explosion_cnt: Option<i32>,
     // 種類によらず、サイズは3x8ドット
@@ -45,7 +49,13 @@ impl Bullet {
         self.flying_cnt = 0;
         self.speed = speed;
     }
-    fn update(&mut self, dot_map: &mut DotMap, player: &mut Player, audio: &Audio) {
+    fn update(
+        &mut self,
+        dot_map: &mut DotMap,
+        player: &mut Player,
+        player_bullet: &mut player::Bullet,
+        audio: &Audio,
+    ) {
         if self.live {
             // 弾が飛翔中
             self.flying_cnt += 1;
@@ -73,6 +83,12 @@ impl Bullet {
             BulletType::Plunger => self.update_plunger_sprite(self.pos.y),
             BulletType::Rolling => self.update_rolling_sprite(self.pos.y),
         }
+        // 飛翔中のプレイヤーの弾と衝突した場合は両方その場で爆発する
+        // (ドットでの当たり判定より先に行い、どちらが先に動いても結果が変わらないようにする)
+        if self.is_hit_player_bullet(player_bullet.shot_pos()) {
+            self.explode_with_player_bullet(dot_map, player_bullet);
+            return;
+        }
         // 赤線に着弾
         if canvas::GAME_HEIGHT - 1 <= self.pos.y + 7 {
             // はみださないようにする
@@ -94,11 +110,37 @@ impl Bullet {
                 }
             }
             self.pos.x -= 3;
-            self.pos.y += 3;
+            // 移動量が大きい場合も画面からはみださないようにする
+            self.pos.y = (self.pos.y + 3).min(canvas::GAME_HEIGHT - 8);
             self.create_explosion_effect(dot_map);
             return;
         }
     }
+    // プレイヤーの弾の見えている部分(1x4ドット)と自身(3x8ドット)が重なっていたら真
+    fn is_hit_player_bullet(&self, shot_pos: Option<Vec2>) -> bool {
+        if !self.live {
+            return false;
+        }
+        match shot_pos {
+            Some(shot_pos) => {
+                self.pos.x <= shot_pos.x
+                    && shot_pos.x < self.pos.x + 3
+                    && shot_pos.y < self.pos.y + 8
+                    && self.pos.y < shot_pos.y + 4
+            }
+            None => false,
+        }
+    }
+    // プレイヤーの弾と自身をそれぞれの爆発エフェクトに置き換える(自身の描画は消されていなければならない)
+    fn explode_with_player_bullet(
+        &mut self,
+        dot_map: &mut DotMap,
+        player_bullet: &mut player::Bullet,
+    ) {
+        player_bullet.explode(dot_map);
+        self.pos.x -= 3;
+        self.create_explosion_effect(dot_map);
+    }
     fn draw(&self, dot_map: &mut DotMap) {
         if !self.live || self.explosion_cnt != None {
             return;
@@ -175,6 +217,13 @@ struct TableManage {
     table: Vec<usize>,
 }
 impl TableManage {
+    // 1から始まる列番号の表から作る
+    fn new(columns: &[usize]) -> Self {
+        TableManage {
+            i: 0,
+            table: columns.iter().map(|x| x - 1).collect(),
+        }
+    }
     // 前回利用した表の値の次の値を返す
     fn take(&mut self) -> usize {
         let value = self.table[self.i];
@@ -194,7 +243,7 @@ pub struct BulletManage {
     // bulets[1]: plunger
     // ねじ型(プレイヤーを狙う)
     // bulets[2]: rolling
-    bullets: Vec<Bullet>,
+    pub bullets: Vec<Bullet>,
     // 発射列表
     plunger_shot_column_table: TableManage,
     squiggly_shot_column_table: TableManage,
@@ -202,8 +251,17 @@ pub struct BulletManage {
     reload_cnt: i32,
     // 弾の移動速度(移動量)
     speed: i32,
+    // 獲得点数で決まる発射間隔から差し引くカウントと、発射間隔の下限
+    reload_reduction: i32,
+    min_reload_cnt: i32,
     // 射撃禁止状態の残りカウント
     ban_fire_cnt: Option<i32>,
+    // 各種類の弾を撃つならば真(bulletsと同じ順)
+    shot_types: [bool; 3],
+    // 弾の動きのルール
+    ruleset: Ruleset,
+    // Accurateで今回のtickに処理する弾のインデックス番号
+    turn: usize,
 }
 impl BulletManage {
     pub fn new(explosion_sprite: Vec<u8>) -> Self {
@@ -214,26 +272,24 @@ impl BulletManage {
 
         BulletManage {
             bullets,
-            plunger_shot_column_table: TableManage {
-                i: 0,
-                table: vec![1, 7, 1, 1, 1, 4, 11, 1, 6, 3, 1, 1, 11, 9, 2, 8]
-                    .iter()
-                    .map(|x| x - 1)
-                    .collect(),
-            },
-            squiggly_shot_column_table: TableManage {
-                i: 0,
-                table: vec![11, 1, 6, 3, 1, 1, 11, 9, 2, 8, 2, 11, 4, 7, 10]
-                    .iter()
-                    .map(|x| x - 1)
-                    .collect(),
-            },
+            plunger_shot_column_table: TableManage::new(&[
+                1, 7, 1, 1, 1, 4, 11, 1, 6, 3, 1, 1, 11, 9, 2, 8,
+            ]),
+            squiggly_shot_column_table: TableManage::new(&[
+                11, 1, 6, 3, 1, 1, 11, 9, 2, 8, 2, 11, 4, 7, 10,
+            ]),
             reload_cnt: 48, // 0x30 * 1.5
             speed: 1,
+            reload_reduction: 0,
+            min_reload_cnt: 0,
             ban_fire_cnt: None,
+            shot_types: [true; 3],
+            ruleset: Ruleset::Standard,
+            turn: 0,
         }
     }
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, params: &StageParams) {
+        self.set_params(params);
         // 開始から一定時間は発射しない
         self.ban_fire_cnt = Some(120);
         for b in self.bullets.iter_mut() {
@@ -244,12 +300,22 @@ impl BulletManage {
         &mut self,
         dot_map: &mut DotMap,
         player: &mut Player,
+        player_bullet: &mut player::Bullet,
         alien: &Alien,
-        score: i32,
+        ufo_live: bool,
         audio: &Audio,
     ) {
         // 獲得点数に応じて発射頻度を変える
-        self.set_reload_cnt(score);
+        self.set_reload_cnt(player_bullet.score);
+        if self.ruleset == Ruleset::Accurate {
+            // 3種類の弾を1tickに1つずつ順番に処理する
+            self.turn = (self.turn + 1) % ruleset::SHOT_TURN_NUM;
+            // エイリアンが少なくなると飛んでいる弾も速くなる
+            let delta = ruleset::shot_delta(alien.live_num);
+            for b in self.bullets.iter_mut().filter(|b| b.live) {
+                b.speed = delta;
+            }
+        }
         if let Some(cnt) = self.ban_fire_cnt {
             if cnt < 0 {
                 self.ban_fire_cnt = None;
@@ -263,59 +329,120 @@ impl BulletManage {
                 self.ban_fire_cnt = Some(cnt + 80);
             } else if alien.explosion.effect_cnt == None {
                 // プレイヤーとエイリアンのどちらも爆発中でない場合に発射処理を行う
-                self.which_fire(player, alien);
+                self.which_fire(player, alien, ufo_live);
             }
         }
         for i in 0..self.bullets.len() {
-            self.bullets[i].update(dot_map, player, audio);
+            // Accurateでは順番が来た弾だけを動かす(爆発エフェクトは毎tick進める)
+            if self.ruleset == Ruleset::Accurate && self.bullets[i].live && i != self.turn {
+                continue;
+            }
+            self.bullets[i].update(dot_map, player, player_bullet, audio);
         }
         self.draw(dot_map);
     }
+    // プレイヤーの弾が移動した後に、エイリアンの弾との衝突を判定する
+    // プレイヤーの弾がこのフレームで障害物に当たっていた場合は、当たった位置で判定する
+    pub fn collide_player_bullet(
+        &mut self,
+        dot_map: &mut DotMap,
+        player_bullet: &mut player::Bullet,
+    ) {
+        let shot_pos = player_bullet
+            .shot_pos()
+            .or_else(|| player_bullet.hit_shot_pos());
+        for bullet in self.bullets.iter_mut() {
+            if bullet.is_hit_player_bullet(shot_pos) {
+                // 描画済みの弾を消してから爆発させる
+                bullet.erase_shifted(dot_map, bullet.pos);
+                bullet.explode_with_player_bullet(dot_map, player_bullet);
+            }
+        }
+    }
+    // ステージの難易度を設定する
+    pub fn set_params(&mut self, params: &StageParams) {
+        self.speed = params.bullet_speed;
+        self.reload_reduction = params.reload_reduction;
+        self.min_reload_cnt = params.min_reload_cnt;
+    }
+    // 撃たせる弾の種類を設定する(rolling, plunger, squigglyの順)
+    pub fn set_shot_types(&mut self, shot_types: [bool; 3]) {
+        self.shot_types = shot_types;
+    }
+    pub fn set_ruleset(&mut self, ruleset: Ruleset) {
+        self.ruleset = ruleset;
+    }
+    // squiggly shotが画面上にあれば真(AccurateではUFOと同時には出ない)
+    pub fn squiggly_live(&self) -> bool {
+        self.bullets[2].live
+    }
+    // plunger shot、squiggly shotの発射列表(1から始まる列番号)を設定する
+    pub fn set_shot_tables(&mut self, plunger_columns: &[usize], squiggly_columns: &[usize]) {
+        self.plunger_shot_column_table = TableManage::new(plunger_columns);
+        self.squiggly_shot_column_table = TableManage::new(squiggly_columns);
+    }
     fn draw(&self, dot_map: &mut DotMap) {
         for i in 0..self.bullets.len() {
             self.bullets[i].draw(dot_map);
         }
     }
     // どのエイリアンがどの種類の弾を撃つか決める
-    fn which_fire(&mut self, player: &Player, alien: &Alien) {
-        let seed = (player.pos.x + alien.ref_alien_pos.x).abs() as usize % 3;
-        // 自身が画面上に無く、かつ他2種の弾が発射してから一定時間経過した後
-        // rolling shot(自機を狙う弾)
-        if seed == 0 && !self.bullets[seed].live && self.bullets[seed].explosion_cnt == None {
-            if (!self.bullets[1].live || self.reload_cnt < self.bullets[1].flying_cnt)
-                && (!self.bullets[2].live || self.reload_cnt < self.bullets[2].flying_cnt)
-            {
-                // プレイヤーに近い列のエイリアンに生き残りがいたら
-                if let Some(i) = alien.alien_index_near_x(player.pos.x) {
-                    // そのエイリアンからrolling shot(自機を狙う)発射
-                    self.bullets[seed].fire(alien.index2pos(i), self.speed);
-                }
+    fn which_fire(&mut self, player: &Player, alien: &Alien, ufo_live: bool) {
+        let seed = match self.ruleset {
+            Ruleset::Standard => (player.pos.x + alien.ref_alien_pos.x).abs() as usize % 3,
+            // アーケード版では順番が来た弾の発射処理を行う
+            Ruleset::Accurate => self.turn,
+        };
+        // 撃たない種類の弾が選ばれた場合は何もしない
+        if !self.shot_types[seed] {
+            return;
+        }
+        if self.ruleset == Ruleset::Accurate {
+            // 最後の1匹はplunger shotを撃たない
+            // squiggly shotはUFOと同じ枠を使うので、UFOが出ている間は撃たない
+            if (seed == 1 && alien.live_num == 1) || (seed == 2 && ufo_live) {
+                return;
             }
-        } else if seed == 1 && !self.bullets[seed].live && self.bullets[seed].explosion_cnt == None
+        }
+        // 自身が画面上に無く、かつ他2種の弾が発射してから一定時間経過した後
+        if self.bullets[seed].live
+            || self.bullets[seed].explosion_cnt.is_some()
+            || !(0..3).filter(|i| *i != seed).all(|i| self.is_reloaded(i))
         {
-            // 自身が画面上に無く、かつ他2種の弾が発射してから一定時間経過した後
+            return;
+        }
+        let index = match seed {
+            // rolling shot(自機を狙う弾)
+            // プレイヤーに近い列のエイリアンに生き残りがいたら、そのエイリアンから発射
+            0 => alien.alien_index_near_x(player.pos.x),
             // plunger shot(十字架、ピストン弾)
-            if (!self.bullets[0].live || self.reload_cnt < self.bullets[0].flying_cnt)
-                && (!self.bullets[2].live || self.reload_cnt < self.bullets[2].flying_cnt)
-            {
-                if let Some(i) = alien.column2index(self.plunger_shot_column_table.take()) {
-                    // plunger shot発射
-                    self.bullets[seed].fire(alien.index2pos(i), self.speed);
-                }
-            }
-        } else if !self.bullets[seed].live && self.bullets[seed].explosion_cnt == None {
-            // 自身が画面上に無く、かつ他2種の弾が発射してから一定時間経過した後
-            if (!self.bullets[0].live || self.reload_cnt < self.bullets[0].flying_cnt)
-                && (!self.bullets[1].live || self.reload_cnt < self.bullets[1].flying_cnt)
-            {
-                if let Some(i) = alien.column2index(self.squiggly_shot_column_table.take()) {
-                    // squiggly shot発射
-                    self.bullets[seed].fire(alien.index2pos(i), self.speed);
-                }
-            }
+            1 => alien.column2index(self.plunger_shot_column_table.take()),
+            // squiggly shot
+            _ => alien.column2index(self.squiggly_shot_column_table.take()),
+        };
+        if let Some(i) = index {
+            let speed = match self.ruleset {
+                Ruleset::Standard => self.speed,
+                Ruleset::Accurate => ruleset::shot_delta(alien.live_num),
+            };
+            self.bullets[seed].fire(alien.index2pos(i), speed);
+        }
+    }
+    // インデックス番号の弾が画面上に無いか、発射してから発射間隔より長く飛んでいれば真
+    // (Accurateでは移動した回数が発射間隔以上であれば真)
+    fn is_reloaded(&self, i: usize) -> bool {
+        let bullet = &self.bullets[i];
+        match self.ruleset {
+            Ruleset::Standard => !bullet.live || self.reload_cnt < bullet.flying_cnt,
+            Ruleset::Accurate => !bullet.live || self.reload_cnt <= bullet.flying_cnt,
         }
     }
     fn set_reload_cnt(&mut self, score: i32) {
+        // Accurateではアーケード版の発射間隔をそのまま使う
+        if self.ruleset == Ruleset::Accurate {
+            self.reload_cnt = ruleset::reload_rate(score);
+            return;
+        }
         self.reload_cnt = match score {
             0..=200 => 48,
             201..=1600 => 32,  // 16 x 2
@@ -323,7 +450,9 @@ impl BulletManage {
             3201..=4800 => 16, // 8 x 2
             4801.. => 14,      // 7 x 2
             _ => 0,
-        }
+        };
+        // ステージの難易度に応じて短くする
+        self.reload_cnt = (self.reload_cnt - self.reload_reduction).max(self.min_reload_cnt);
     }
 }
 
@@ -365,6 +494,16 @@ impl ArraySprite for Explosion {
     }
 }
 
+// プレイヤー交代時に保存するエイリアンの隊列
+pub struct Formation {
+    live: Vec<bool>,
+    types: Vec<usize>,
+    live_num: i32,
+    ref_alien_pos: Vec2,
+    // 横方向の移動量(移動方向)
+    speed_x: i32,
+}
+
 pub struct Alien {
     // リファレンスエイリアンの座標
     pub ref_alien_pos: Vec2,
@@ -380,11 +519,13 @@ pub struct Alien {
     // エイリアンの移動量
     speed: Vec2,
     // エイリアンの生存状態
-    live: Vec<bool>,
+    pub live: Vec<bool>,
+    // エイリアンの種類(0: 下段のタコ, 1: 中段のカニ, 2: 上段のイカ)
+    types: Vec<usize>,
     // 生きているエイリアンの数
     pub live_num: i32,
-    // ステージ2から9までのリファレンスエイリアンの位置
-    table_init_pos_y: Vec<i32>,
+    // 横方向の移動量(最後の1匹はこれより1大きくなる)
+    march_dx: i32,
     // 再生するサウンドのインデックス番号
     se_index: usize,
     // 前回音を再生してからの経過tick数
@@ -414,13 +555,6 @@ impl Alien {
         sprite_list.push(middle_sprite1);
         sprite_list.push(high_sprite0);
         sprite_list.push(high_sprite1);
-        let table_init_pos_y: Vec<i32> = vec![88, 72, 64, 64, 64, 56, 56, 56]
-            .iter()
-            .map(|y| canvas::GAME_HEIGHT - y)
-            .collect();
-        if table_init_pos_y.len() != 8 {
-            panic!("エイリアンの初期位置表の要素数は8にしてください。");
-        }
         Alien {
             ref_alien_pos: Vec2::new(0, 0),
             pre_ref_alien_pos: Vec2::new(0, 0),
@@ -434,31 +568,64 @@ impl Alien {
             i_cursor_alien: 0,
             speed: Vec2::new(2, 0),
             live: vec![true; 55],
+            types: (0..55)
+                .map(|i| level::classic_alien_type(i / level::COLUMN_NUM))
+                .collect(),
             live_num: 55,
-            table_init_pos_y,
+            march_dx: 2,
             se_index: 0,
             se_ticks: 0,
             se_permit: Self::se_permit(55),
         }
     }
-    // エイリアンを初期化する
-    pub fn reset(&mut self, stage: usize) {
-        self.live = vec![true; 55];
-        self.live_num = 55;
+    // 引数の隊列(各マスのエイリアンの種類、Noneは空きマス)でエイリアンを初期化する
+    pub fn reset(&mut self, params: &StageParams, formation: &[Option<usize>]) {
+        self.live = formation.iter().map(|cell| cell.is_some()).collect();
+        self.types = formation
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| cell.unwrap_or(level::classic_alien_type(i / level::COLUMN_NUM)))
+            .collect();
+        self.live_num = self.live.iter().filter(|live| **live).count() as i32;
         self.show_sprite = true;
         self.i_cursor_alien = 0;
-        self.speed = Vec2::new(2, 0);
+        self.march_dx = params.march_dx;
+        self.speed = Vec2::new(self.march_dx, 0);
         self.se_index = 0;
-        self.se_ticks = Self::se_permit(55) + 1;
-        self.se_permit = Self::se_permit(55);
+        self.se_ticks = Self::se_permit(self.live_num) + 1;
+        self.se_permit = Self::se_permit(self.live_num);
 
         // ステージ数によって初期位置が決まる
-        self.ref_alien_pos.x = 24;
-        self.ref_alien_pos.y = if 1 < stage {
-            self.table_init_pos_y[(stage - 2) % 8]
-        } else {
-            canvas::GAME_HEIGHT - 112
-        };
+        self.ref_alien_pos.x = level::START_POS_X;
+        self.ref_alien_pos.y = params.start_pos_y;
+        self.pre_ref_alien_pos = self.ref_alien_pos;
+    }
+    // 現在の隊列を保存する
+    pub fn save_formation(&self) -> Formation {
+        Formation {
+            live: self.live.clone(),
+            types: self.types.clone(),
+            live_num: self.live_num,
+            ref_alien_pos: self.ref_alien_pos,
+            speed_x: self.speed.x,
+        }
+    }
+    // 保存した隊列から再開する
+    pub fn restore_formation(&mut self, formation: &Formation, params: &StageParams) {
+        self.march_dx = params.march_dx;
+        self.live = formation.live.clone();
+        self.types = formation.types.clone();
+        self.live_num = formation.live_num;
+        self.show_sprite = true;
+        self.i_cursor_alien = 0;
+        self.speed = Vec2::new(formation.speed_x, 0);
+        self.se_index = 0;
+        self.se_permit = Self::se_permit(self.live_num);
+        self.se_ticks = self.se_permit + 1;
+        // 画面は作り直されるので爆発エフェクトは引き継がない
+        self.explosion.effect_cnt = None;
+
+        self.ref_alien_pos = formation.ref_alien_pos;
         self.pre_ref_alien_pos = self.ref_alien_pos;
     }
     pub fn update(&mut self, dot_map: &mut DotMap, player_exploding: bool, audio: &Audio) {
@@ -477,6 +644,9 @@ impl Alien {
             let mut alien_top_pos = self.index2pos(self.i_cursor_alien);
             alien_top_pos.y -= 8;
             self.erase(dot_map, alien_top_pos);
+            // シールドと重なったら削る
+            let alien_pos = self.index2pos(self.i_cursor_alien);
+            shield::alien_moved(dot_map, alien_pos);
             // 移動後を描画する
             self.array_sprite(dot_map);
         }
@@ -521,6 +691,10 @@ impl Alien {
             }
         }
     }
+    // 最後の1匹が速度を上げて右へ移動中であれば真を返す
+    pub fn top_speed(&self) -> bool {
+        self.march_dx < self.speed.x
+    }
     // 一番下のエイリアンがプレイヤーの高さまで侵攻したら真を返す
     pub fn invaded(&self) -> bool {
         canvas::GAME_HEIGHT - 24 <= self.index2pos(self.i_cursor_alien).y
@@ -531,7 +705,7 @@ impl Alien {
         for char_y in 2..23 {
             // 右の壁のドットに何かが存在したら
             if dot_map.map[char_y][213] != 0 {
-                self.speed = Vec2::new(-2, 8);
+                self.speed = Vec2::new(-self.march_dx, 8);
                 return;
             }
         }
@@ -540,9 +714,9 @@ impl Alien {
             if dot_map.map[char_y][9] != 0 {
                 // エイリアンが最後の1匹のときは速度を上げる
                 self.speed = if self.live_num == 1 {
-                    Vec2::new(3, 8)
+                    Vec2::new(self.march_dx + 1, 8)
                 } else {
-                    Vec2::new(2, 8)
+                    Vec2::new(self.march_dx, 8)
                 };
                 return;
             }
@@ -554,7 +728,7 @@ impl Alien {
     pub fn remove(&mut self, dot_map: &mut DotMap, i: usize, audio: &Audio) {
         self.live[i] = false;
         self.se_permit = Self::se_permit(self.live_num);
-        let width = self.sprite_list[2 * Alien::ret_alien_type(i)].len();
+        let width = self.sprite_list[2 * self.types[i]].len();
         let alien_pos = self.index2pos(i);
 
         let char_y = (alien_pos.y / 8) as usize;
@@ -648,7 +822,7 @@ impl Alien {
     }
 
     // エイリアンのインデックス番号から座標を返す
-    fn index2pos(&self, i: usize) -> Vec2 {
+    pub fn index2pos(&self, i: usize) -> Vec2 {
         let dx = i as i32 % 11;
         let dy = i as i32 / 11;
         // リファレンスエイリアンと同期済
@@ -665,24 +839,18 @@ impl Alien {
             )
         }
     }
-    pub fn index2score(i: usize) -> i32 {
-        match Alien::ret_alien_type(i) {
+    // エイリアンの種類(0: 下段のタコ, 1: 中段のカニ, 2: 上段のイカ)
+    pub fn index2type(&self, i: usize) -> usize {
+        self.types[i]
+    }
+    pub fn index2score(&self, i: usize) -> i32 {
+        match self.types[i] {
             0 => 10,
             1 => 20,
             2 => 30,
             _ => panic!("エイリアンを指すインデックス番号が不正です。"),
         }
     }
-    // インデックス番号から、下2段は0、中2段は1、上1段は2を返す
-    fn ret_alien_type(i: usize) -> usize {
-        let row = i / 11;
-        match row {
-            0 | 1 => 0,
-            2 | 3 => 1,
-            4 => 2,
-            _ => panic!("エイリアンを指すインデックス番号が不正です。"),
-        }
-    }
 }
 
 impl ArraySprite for Alien {
@@ -694,6 +862,143 @@ impl ArraySprite for Alien {
     fn sprite(&self) -> &[u8] {
         // 2つの状態のスプライトのどちらを描画するか
         let sprite_type: usize = if self.show_sprite { 0 } else { 1 };
-        &self.sprite_list[2 * Alien::ret_alien_type(self.i_cursor_alien) + sprite_type]
+        &self.sprite_list[2 * self.types[self.i_cursor_alien] + sprite_type]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::game::Game;
+    use crate::player::PLAYER_BULLET_DELTA;
+
+    // プレイヤーの弾を発射してticks回移動させた、エイリアンやシールドのないゲーム
+    fn game_with_shot(ticks: usize, audio: &Audio) -> Game {
+        let mut game = Game::new(GameConfig::default());
+        game.map.all_clear();
+        for _ in 0..=ticks {
+            update_player_bullet(&mut game, audio);
+        }
+        assert!(game.player_bullet.shot_pos().is_some());
+        game
+    }
+    fn update_player_bullet(game: &mut Game, audio: &Audio) {
+        game.player_bullet.update(
+            &mut game.map,
+            &mut game.player,
+            &mut game.ufo,
+            &mut game.alien,
+            true,
+            audio,
+        );
+    }
+    // 移動後に左上がposになるエイリアンの弾を置く
+    fn place_alien_bullet(game: &mut Game, pos: Vec2) {
+        let bullet = &mut game.alien_bullets.bullets[0];
+        bullet.speed = 4;
+        bullet.pos = Vec2::new(pos.x, pos.y - bullet.speed);
+        bullet.live = true;
+    }
+    fn update_alien_bullet(game: &mut Game, audio: &Audio) {
+        game.alien_bullets.bullets[0].update(
+            &mut game.map,
+            &mut game.player,
+            &mut game.player_bullet,
+            audio,
+        );
+    }
+
+    #[test]
+    fn head_on_shots_cancel() {
+        let audio = Audio::silent();
+        let mut game = game_with_shot(3, &audio);
+        let shot = game.player_bullet.shot_pos().unwrap();
+        // 弾の中央の列がプレイヤーの弾と重なる
+        place_alien_bullet(&mut game, Vec2::new(shot.x - 1, shot.y - 4));
+        update_alien_bullet(&mut game, &audio);
+        let bullet = &game.alien_bullets.bullets[0];
+        assert!(!bullet.live);
+        assert!(bullet.explosion_cnt.is_some());
+        assert!(game.player_bullet.shot_pos().is_none());
+    }
+
+    #[test]
+    fn near_miss_does_not_cancel() {
+        let audio = Audio::silent();
+        let mut game = game_with_shot(3, &audio);
+        let shot = game.player_bullet.shot_pos().unwrap();
+        // 1ドット右にずれていれば当たらない
+        place_alien_bullet(&mut game, Vec2::new(shot.x + 1, shot.y - 4));
+        update_alien_bullet(&mut game, &audio);
+        game.alien_bullets
+            .collide_player_bullet(&mut game.map, &mut game.player_bullet);
+        assert!(game.alien_bullets.bullets[0].live);
+        assert!(game.player_bullet.shot_pos().is_some());
+    }
+
+    // プレイヤーの弾をシールドに当てたゲームと、当たった位置
+    fn game_with_shield_hit(audio: &Audio) -> (Game, Vec2) {
+        let mut game = game_with_shot(0, audio);
+        // 弾の移動先にシールドのドットを置く
+        let shot = game.player_bullet.shot_pos().unwrap();
+        let char_y = ((shot.y - PLAYER_BULLET_DELTA) / 8) as usize;
+        game.map.map[char_y][shot.x as usize] = 0xff;
+        game.map.map[char_y + 1][shot.x as usize] = 0xff;
+        update_player_bullet(&mut game, audio);
+        let hit = game.player_bullet.hit_shot_pos().unwrap();
+        game.map.all_clear();
+        (game, hit)
+    }
+
+    #[test]
+    fn shield_hit_cancels_in_same_frame() {
+        let audio = Audio::silent();
+        let (mut game, hit) = game_with_shield_hit(&audio);
+        place_alien_bullet(&mut game, Vec2::new(hit.x - 1, hit.y));
+        game.alien_bullets.bullets[0].pos.y = hit.y;
+        game.alien_bullets
+            .collide_player_bullet(&mut game.map, &mut game.player_bullet);
+        assert!(!game.alien_bullets.bullets[0].live);
+    }
+
+    #[test]
+    fn no_cancel_one_frame_after_shield_hit() {
+        let audio = Audio::silent();
+        let (mut game, hit) = game_with_shield_hit(&audio);
+        // 次のフレームで当たった位置を通るエイリアンの弾は、爆発済みの弾とは相殺しない
+        place_alien_bullet(&mut game, Vec2::new(hit.x - 1, hit.y));
+        update_alien_bullet(&mut game, &audio);
+        assert!(game.alien_bullets.bullets[0].live);
+        update_player_bullet(&mut game, &audio);
+        game.alien_bullets
+            .collide_player_bullet(&mut game.map, &mut game.player_bullet);
+        assert!(game.alien_bullets.bullets[0].live);
+    }
+
+    // Accurateで、turnの種類の弾の発射処理を行う
+    fn accurate_fire(game: &mut Game, turn: usize, ufo_live: bool) -> bool {
+        let bullets = &mut game.alien_bullets;
+        bullets.set_ruleset(Ruleset::Accurate);
+        bullets.turn = turn;
+        bullets.which_fire(&game.player, &game.alien, ufo_live);
+        bullets.bullets[turn].live
+    }
+
+    #[test]
+    fn accurate_last_alien_does_not_fire_plunger() {
+        let mut game = Game::new(GameConfig::default());
+        assert!(accurate_fire(&mut game, 1, false));
+        let mut game = Game::new(GameConfig::default());
+        game.alien.live_num = 1;
+        assert!(!accurate_fire(&mut game, 1, false));
+    }
+
+    #[test]
+    fn accurate_no_squiggly_while_ufo_live() {
+        let mut game = Game::new(GameConfig::default());
+        assert!(accurate_fire(&mut game, 2, false));
+        let mut game = Game::new(GameConfig::default());
+        assert!(!accurate_fire(&mut game, 2, true));
     }
 }
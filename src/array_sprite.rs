@@ -1,5 +1,6 @@
 use crate::dot_map::DotMap;
 use crate::math::Vec2;
+use crate::sprite;
 
 pub trait ArraySprite {
     //  スプライトを置く位置
@@ -96,3 +97,11 @@ pub fn erase(dot_map: &mut Vec<Vec<u8>>, pos: Vec2, sprite: &[u8]) {
         dot_map[char_y][pos.x as usize + dx] = 0;
     }
 }
+
+// 文字列を文字単位(8x8ドット)で左から並べて描画する(上書き)
+pub fn array_text(dot_map: &mut Vec<Vec<u8>>, mut pos: Vec2, text: &str, char_sprite: &[Vec<u8>]) {
+    for c in text.chars() {
+        array_sprite(dot_map, pos, &char_sprite[sprite::char2index(c)]);
+        pos.x += 8;
+    }
+}
@@ -1,3 +1,4 @@
+use crate::mixer::{Bus, Mixer};
 use anyhow::anyhow;
 use js_sys::ArrayBuffer;
 use wasm_bindgen::{JsCast, JsValue};
@@ -31,7 +32,10 @@ pub async fn ret_audio() -> Audio {
 
 #[derive(Clone)]
 pub struct Audio {
-    context: AudioContext,
+    // 音を鳴らさない場合(ヘッドレス実行時など)はNone
+    context: Option<AudioContext>,
+    // バスごとの音量
+    pub mixer: Mixer,
     pub invader_move: Vec<Sound>,
     pub player_shot: Option<Sound>,
     pub invader_explosion: Option<Sound>,
@@ -40,10 +44,31 @@ pub struct Audio {
     pub ufo_explosion: Option<Sound>,
 }
 
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Audio {
     pub fn new() -> Self {
+        let context = create_audio_context().unwrap();
+        Audio {
+            mixer: Mixer::new(Some(&context)),
+            context: Some(context),
+            invader_move: Vec::new(),
+            player_shot: None,
+            invader_explosion: None,
+            player_explosion: None,
+            ufo_flying: None,
+            ufo_explosion: None,
+        }
+    }
+    // 音を一切鳴らさない(ブラウザのAPIを使わない)
+    pub fn silent() -> Self {
         Audio {
-            context: create_audio_context().unwrap(),
+            context: None,
+            mixer: Mixer::new(None),
             invader_move: Vec::new(),
             player_shot: None,
             invader_explosion: None,
@@ -54,45 +79,45 @@ impl Audio {
     }
     // ファイル名から音声データを取得
     pub async fn load_sound(&self, filename: &str) -> Result<Sound, ()> {
+        let context = self.context.as_ref().ok_or(())?;
         let array_buffer = fetch_array_buffer(filename).await.unwrap();
-        let audio_buffer = decode_audio_data(&self.context, &array_buffer)
+        let audio_buffer = decode_audio_data(context, &array_buffer)
             .await
             .map_err(|err| log::info!("error converting fetch to Response {:#?}", err))?;
-        // 音声データ毎にデフォルトの音量を設定
-        let default_volume: f32 = match filename {
-            "sound/ufo_flying.wav" | "sound/ufo_explosion.wav" => 0.03,
-            "sound/shoot.wav" => 0.1,
-            "sound/invader_killed.wav" => 0.1,
-            _ => 0.4,
+        // 音声データ毎に出力するバスを設定(音量はバスの音量を掛けて鳴らす)
+        let bus = match filename {
+            "audio/ufo_flying.wav" | "audio/ufo_explosion.wav" => Bus::Ufo,
+            "audio/shoot.wav" | "audio/invader_killed.wav" | "audio/player_explosion.wav" => {
+                Bus::Effects
+            }
+            _ => Bus::March,
         };
+        // 以前の音声データ毎の音量はファイル名が一致せず使われていなかったので、すべて同じ音量で鳴らす
         Ok(Sound {
             buffer: audio_buffer,
-            default_volume,
-            volume: default_volume,
+            volume: 0.4,
+            bus,
         })
     }
     //サウンドを一度だけ再生
     pub fn play_once_sound(&self, sound: &Sound) {
-        self.play_sound(&sound.buffer, sound.volume, false);
+        self.play_sound(sound, false);
     }
     //サウンドをループ再生
-    pub fn play_looping_sound(&self, sound: &Sound) -> AudioBufferSourceNode {
-        self.play_sound(&sound.buffer, sound.volume, true)
+    pub fn play_looping_sound(&self, sound: &Sound) -> Option<AudioBufferSourceNode> {
+        self.play_sound(sound, true)
     }
 
-    fn play_sound(
-        &self,
-        buffer: &AudioBuffer,
-        volume: f32,
-        looping: bool,
-    ) -> AudioBufferSourceNode {
-        let track_source = create_track_source(&self.context, buffer);
-        let gain_node = self.context.create_gain().unwrap();
+    fn play_sound(&self, sound: &Sound, looping: bool) -> Option<AudioBufferSourceNode> {
+        let context = self.context.as_ref()?;
+        let track_source = create_track_source(context, &sound.buffer);
+        let gain_node = context.create_gain().unwrap();
         // 音量設定
-        gain_node.gain().set_value(volume);
+        gain_node.gain().set_value(sound.volume);
         track_source.connect_with_audio_node(&gain_node).unwrap();
+        // 種類ごとのバスを通して出力する
         gain_node
-            .connect_with_audio_node(&self.context.destination())
+            .connect_with_audio_node(self.mixer.input(sound.bus)?)
             .unwrap();
         // ループ処理
         if looping {
@@ -103,80 +128,22 @@ impl Audio {
             .start()
             .map_err(|err| log::info!("Could not start sound! {:#?}", err))
             .unwrap();
-        track_source
-    }
-    // 音量を初期値に戻す
-    pub fn reset_volume(&mut self) {
-        for invader in self.invader_move.iter_mut() {
-            invader.volume = invader.default_volume;
-        }
-        if let Some(sound) = self.invader_explosion.as_mut() {
-            sound.volume = sound.default_volume;
-        }
-        if let Some(sound) = self.player_shot.as_mut() {
-            sound.volume = sound.default_volume;
-        }
-        if let Some(sound) = self.player_explosion.as_mut() {
-            sound.volume = sound.default_volume;
-        }
-        if let Some(sound) = self.ufo_flying.as_mut() {
-            sound.volume = sound.default_volume;
-        }
-        if let Some(sound) = self.ufo_explosion.as_mut() {
-            sound.volume = sound.default_volume;
-        }
-    }
-    // 音量を上げる
-    pub fn all_volume_up(&mut self) {
-        self.all_volume_change(0.1);
+        Some(track_source)
     }
-    // 音量を下げる
-    pub fn all_volume_down(&mut self) {
-        self.all_volume_change(-0.1);
-    }
-
-    // 値が一定範囲内に収まるように変更する
-    fn all_volume_change(&mut self, diff: f32) {
-        // 最小音量値
-        let min = 0.;
-        // 最大音量値
-        let max = 1.5;
-        for invader in self.invader_move.iter_mut() {
-            let volume = invader.volume + diff;
-            invader.volume = Self::limit_value(volume, min, max);
-        }
-        if let Some(sound) = self.invader_explosion.as_mut() {
-            let volume = sound.volume + diff;
-            sound.volume = Self::limit_value(volume, min, max);
-        }
-        if let Some(sound) = self.player_shot.as_mut() {
-            let volume = sound.volume + diff;
-            sound.volume = Self::limit_value(volume, min, max);
-        }
-        if let Some(sound) = self.player_explosion.as_mut() {
-            let volume = sound.volume + diff;
-            sound.volume = Self::limit_value(volume, min, max);
-        }
-        if let Some(sound) = self.ufo_flying.as_mut() {
-            let volume = sound.volume + diff * 0.1;
-            sound.volume = Self::limit_value(volume, min, max);
-        }
-        if let Some(sound) = self.ufo_explosion.as_mut() {
-            let volume = sound.volume + diff;
-            sound.volume = Self::limit_value(volume, min, max);
+    // 再生中の音を含め、すべての音を一時停止する
+    pub fn suspend(&self) {
+        if let Some(context) = self.context.as_ref() {
+            if let Err(err) = context.suspend() {
+                log::info!("Could not suspend audio! {:#?}", err);
+            }
         }
     }
-    // 指定した範囲に収めた値を返す
-    fn limit_value(value: f32, min: f32, max: f32) -> f32 {
-        if min > max {
-            log::info!("範囲指定に間違いがあります。");
-            value
-        } else if value < min {
-            min
-        } else if max < value {
-            max
-        } else {
-            value
+    // 一時停止した音の再生を再開する
+    pub fn resume(&self) {
+        if let Some(context) = self.context.as_ref() {
+            if let Err(err) = context.resume() {
+                log::info!("Could not resume audio! {:#?}", err);
+            }
         }
     }
 }
@@ -184,8 +151,8 @@ impl Audio {
 #[derive(Clone)]
 pub struct Sound {
     buffer: AudioBuffer,
-    volume: f32,         // 再生時の音量
-    default_volume: f32, // 音量の初期値
+    volume: f32, // 再生時の音量
+    bus: Bus,    // 出力するバス
 }
 
 async fn fetch_array_buffer(resource: &str) -> Result<ArrayBuffer, JsValue> {
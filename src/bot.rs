@@ -0,0 +1,194 @@
+use crate::canvas;
+use crate::game::Game;
+use crate::input::KeyDown;
+use crate::math::Rng;
+use crate::player::PLAYER_BULLET_DELTA;
+
+// エイリアンの弾を避けるために先読みするtick数
+const LOOKAHEAD: i32 = 48;
+// 弾を避ける時にプレイヤーの左右に取る余裕(ドット単位)
+const DODGE_MARGIN: i32 = 2;
+// プレイヤーの当たり判定の上端と下端のy座標
+const PLAYER_TOP: i32 = canvas::GAME_HEIGHT - 8 * 3;
+const PLAYER_BOTTOM: i32 = canvas::GAME_HEIGHT - 8 * 2;
+// プレイヤーが移動できる左端と右端(player.rsの移動範囲制限と同じ)
+const PLAYER_MIN_X: i32 = 7;
+const PLAYER_MAX_RIGHT: i32 = canvas::GAME_WIDTH - 7;
+
+// ゲームの状態から毎tickキー入力を作る自動操縦
+// デモプレイとヘッドレスでの長時間試験に使う
+pub struct Bot {
+    // シードごとに狙いのずれ方が変わる
+    rng: Rng,
+    // エイリアンを狙う位置のずれ
+    aim_offset: i32,
+    // 次に狙いのずれを変えるまでのtick数
+    retarget_cnt: i32,
+}
+
+impl Bot {
+    pub fn new(seed: u64) -> Self {
+        Bot {
+            rng: Rng::new(seed),
+            aim_offset: 0,
+            retarget_cnt: 0,
+        }
+    }
+    pub fn think(&mut self, game: &Game) -> KeyDown {
+        let mut key = KeyDown::default();
+        let player = &game.player;
+        // 撃破されている間は何もしない
+        if player.explosion_cnt.is_some() {
+            return key;
+        }
+        self.retarget_cnt -= 1;
+        if self.retarget_cnt < 0 {
+            self.retarget_cnt = 30 + (self.rng.next_u64() % 60) as i32;
+            self.aim_offset = (self.rng.next_u64() % 5) as i32 - 2;
+        }
+        // プレイヤーの弾が発射されるx座標
+        let muzzle_x = player.pos.x + 7;
+        let target_x = self.target_x(game);
+        let want = target_x.map_or(0, |x| (x - muzzle_x).signum());
+        // 目標へ近づく、止まる、離れるの順に、弾に当たらない動きを選ぶ
+        // どの動きでも当たる場合は、当たるまでの時間が最も長い動きを選ぶ
+        let candidates = if want == 0 {
+            [0, -1, 1]
+        } else {
+            [want, 0, -want]
+        };
+        let dir = candidates
+            .iter()
+            .copied()
+            .find(|dir| Self::hit_tick(game, *dir).is_none())
+            .unwrap_or_else(|| {
+                candidates
+                    .iter()
+                    .copied()
+                    .max_by_key(|dir| Self::hit_tick(game, *dir))
+                    .unwrap_or(0)
+            });
+        key.left = dir < 0;
+        key.right = 0 < dir;
+        // 狙いが合ったら撃つ(撃てない間は発射ボタンを離し、連射しない設定でも撃てるようにする)
+        key.shot = game.player_bullet.can_fire()
+            && matches!(target_x, Some(x) if (x - muzzle_x).abs() <= 1);
+        key
+    }
+    // 狙うx座標
+    // UFOが出ている時は、次の弾がscore_tableの最高得点に当たる発射数ならばUFOを狙う
+    // それ以外は一番下にいるエイリアンのうちプレイヤーに近いものを狙う
+    fn target_x(&self, game: &Game) -> Option<i32> {
+        let ufo = &game.ufo;
+        if ufo.live && ufo.explosion.show_cnt.is_none() {
+            let best =
+                (0..ufo.score_table.len()).max_by_key(|i| (ufo.score_table[*i], -(*i as i32)));
+            // 得点表は(発射数 - 1)の位置を引くため、次の弾では現在の発射数の位置になる
+            if Some(game.player_bullet.fire_cnt as usize % ufo.score_table.len()) == best {
+                // 弾がUFOの高さに届くまでのtick数だけ先の位置を狙う
+                let ticks = (game.player.pos.y - 8 - 16) / PLAYER_BULLET_DELTA + 1;
+                return Some(ufo.pos.x + ufo.width / 2 + ufo.move_dir * ticks);
+            }
+        }
+        let muzzle_x = game.player.pos.x + 7;
+        let alien = &game.alien;
+        (0..alien.live.len())
+            .filter(|i| alien.live[*i])
+            .map(|i| alien.index2pos(i))
+            .max_by_key(|pos| (pos.y, -(pos.x + 8 - muzzle_x).abs()))
+            .map(|pos| pos.x + 8 + self.aim_offset)
+    }
+    // プレイヤーがdirの方向へ動き続けた場合に、先読みする間にエイリアンの弾に当たるtick数
+    fn hit_tick(game: &Game, dir: i32) -> Option<i32> {
+        let player = &game.player;
+        let max_x = PLAYER_MAX_RIGHT - player.width;
+        let mut hit: Option<i32> = None;
+        for bullet in game.alien_bullets.bullets.iter().filter(|b| b.live) {
+            for t in 1..=LOOKAHEAD {
+                let y = bullet.pos.y + bullet.speed * t;
+                if y + 8 <= PLAYER_TOP {
+                    continue;
+                }
+                if PLAYER_BOTTOM <= y {
+                    break;
+                }
+                let x = (player.pos.x + dir * t).clamp(PLAYER_MIN_X, max_x);
+                if x - DODGE_MARGIN < bullet.pos.x + 3
+                    && bullet.pos.x < x + player.width + DODGE_MARGIN
+                {
+                    hit = Some(hit.map_or(t, |h| h.min(t)));
+                    break;
+                }
+            }
+        }
+        hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Audio;
+    use crate::config::GameConfig;
+    use crate::input::Actions;
+    use crate::math::Vec2;
+
+    // 自動操縦の入力でゲームを進め、各tickの入力を返す
+    fn play(seed: u64, ticks: usize) -> Vec<String> {
+        let audio = Audio::silent();
+        let mut game = Game::new(GameConfig::default());
+        let mut bot = Bot::new(seed);
+        let mut actions = Actions::default();
+        let mut keys = Vec::new();
+        for _ in 0..ticks {
+            let key = bot.think(&game);
+            keys.push(format!("{:?}", key));
+            actions.sample(&key);
+            game.update(&actions, &audio);
+        }
+        keys
+    }
+
+    #[test]
+    fn same_seed_gives_same_inputs() {
+        assert_eq!(play(7, 600), play(7, 600));
+    }
+
+    #[test]
+    fn does_not_fire_while_bullet_is_live() {
+        let audio = Audio::silent();
+        let mut game = Game::new(GameConfig::default());
+        let mut bot = Bot::new(0);
+        let mut actions = Actions::default();
+        let mut fired = false;
+        for _ in 0..600 {
+            let key = bot.think(&game);
+            if !game.player_bullet.can_fire() {
+                assert!(!key.shot);
+            }
+            fired |= key.shot;
+            actions.sample(&key);
+            game.update(&actions, &audio);
+        }
+        assert!(fired);
+    }
+
+    #[test]
+    fn dodges_bullet_about_to_hit() {
+        let mut game = Game::new(GameConfig::default());
+        // 狙う位置をずらさない
+        let mut bot = Bot::new(0);
+        bot.retarget_cnt = 100;
+        // プレイヤーの真上から落ちてくる弾
+        let player_x = game.player.pos.x;
+        let bullet = &mut game.alien_bullets.bullets[0];
+        bullet.pos = Vec2::new(player_x + 7, PLAYER_TOP - 80);
+        bullet.speed = 4;
+        bullet.live = true;
+        assert!(Bot::hit_tick(&game, 0).is_some());
+        let key = bot.think(&game);
+        assert!(key.left != key.right);
+        let dir = if key.left { -1 } else { 1 };
+        assert_eq!(Bot::hit_tick(&game, dir), None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::array_sprite::array_sprite;
+use crate::array_sprite::{array_sprite, array_text};
 use crate::canvas;
 use crate::math::Vec2;
 
@@ -25,8 +25,20 @@ impl BottomArea {
         self.bottom =
             vec![vec![0; canvas::BOTTOM_WIDTH as usize]; (canvas::BOTTOM_HEIGHT / 8) as usize];
     }
-    pub fn draw(&mut self, player_life: i32) {
+    // creditsがNoneの場合はフリープレイと表示する
+    pub fn draw(&mut self, player_life: i32, credits: Option<i32>) {
         self.all_clear();
+        // 右側にクレジット数を表示する(2桁)
+        let credit_text = match credits {
+            Some(credits) => format!("CREDIT {:02}", credits),
+            None => "FREE PLAY".to_string(),
+        };
+        array_text(
+            &mut self.bottom,
+            Vec2::new(136, 8),
+            &credit_text,
+            &self.num_sprite,
+        );
         // 残機の数を表示する(1桁)
         array_sprite(
             &mut self.bottom,
@@ -0,0 +1,166 @@
+use crate::alien::Alien;
+use crate::level;
+use crate::player::ShotResult;
+use crate::stats::StageStats;
+
+// 倍率が1上がるまでの連続命中数
+const HITS_PER_LEVEL: i32 = 5;
+// 倍率の上限
+const MAX_MULTIPLIER: i32 = 5;
+// 1段のエイリアンを全滅させた時のボーナス(倍率を掛ける)
+const ROW_CLEAR_BONUS: i32 = 100;
+// 1発も外さずにステージをクリアした時のボーナス
+const PERFECT_BONUS: i32 = 1000;
+
+// 得点の計算方法
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scoring {
+    // アーケード版と同じ得点
+    Classic,
+    // 連続命中の倍率とボーナスを加える
+    Modern,
+}
+
+impl Scoring {
+    // 設定の保存に使う名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Scoring::Classic => "CLASSIC",
+            Scoring::Modern => "MODERN",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "CLASSIC" => Some(Scoring::Classic),
+            "MODERN" => Some(Scoring::Modern),
+            _ => None,
+        }
+    }
+}
+
+// モダンの得点方式での連続命中の状態
+#[derive(Clone, Default)]
+pub struct Combo {
+    // 外さずに当てた連続数
+    pub streak: i32,
+}
+
+impl Combo {
+    // 得点に掛ける倍率
+    pub fn multiplier(&self) -> i32 {
+        (1 + self.streak / HITS_PER_LEVEL).min(MAX_MULTIPLIER)
+    }
+    // プレイヤーの弾が何かに当たるか画面上部に届いた時に呼び出し、通常の得点に加える点数を返す
+    // 弾が画面上部に届いたら倍率を元に戻す
+    pub fn record_shot(&mut self, result: ShotResult, alien: &Alien) -> i32 {
+        match result {
+            ShotResult::Alien { index, score, .. } => {
+                let multiplier = self.multiplier();
+                self.streak += 1;
+                // 撃破したエイリアンの段に生き残りがいなければ段のボーナスを加える
+                let row = index / level::COLUMN_NUM;
+                let row_cleared = !alien.live
+                    [row * level::COLUMN_NUM..(row + 1) * level::COLUMN_NUM]
+                    .iter()
+                    .any(|live| *live);
+                let row_bonus = if row_cleared { ROW_CLEAR_BONUS } else { 0 };
+                score * (multiplier - 1) + row_bonus * multiplier
+            }
+            ShotResult::Ufo { score } => {
+                let multiplier = self.multiplier();
+                self.streak += 1;
+                score * (multiplier - 1)
+            }
+            ShotResult::Obstacle => 0,
+            ShotResult::Top => {
+                self.streak = 0;
+                0
+            }
+        }
+    }
+    // エイリアンを全滅させた時に呼び出し、1発も外さなかった場合のボーナスを返す
+    pub fn stage_bonus(stats: &StageStats) -> i32 {
+        if 0 < stats.shots && stats.hits == stats.shots {
+            PERFECT_BONUS
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::game::Game;
+
+    fn alien_hit(index: usize, score: i32) -> ShotResult {
+        ShotResult::Alien {
+            index,
+            alien_type: 0,
+            score,
+        }
+    }
+
+    #[test]
+    fn multiplier_steps_every_five_hits() {
+        let alien = Game::new(GameConfig::default()).alien;
+        let mut combo = Combo::default();
+        let mut bonuses = Vec::new();
+        for _ in 0..30 {
+            bonuses.push(combo.record_shot(alien_hit(0, 10), &alien));
+        }
+        // 倍率は当てる前の連続数で決まり、1倍の間はボーナスなし
+        assert!(bonuses[..5].iter().all(|b| *b == 0));
+        assert!(bonuses[5..10].iter().all(|b| *b == 10));
+        assert!(bonuses[10..15].iter().all(|b| *b == 20));
+        assert!(bonuses[20..25].iter().all(|b| *b == 40));
+        // 上限は5倍
+        assert!(bonuses[25..].iter().all(|b| *b == 40));
+        assert_eq!(combo.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn obstacle_keeps_and_top_resets_streak() {
+        let alien = Game::new(GameConfig::default()).alien;
+        let mut combo = Combo { streak: 7 };
+        assert_eq!(combo.record_shot(ShotResult::Obstacle, &alien), 0);
+        assert_eq!(combo.multiplier(), 2);
+        assert_eq!(
+            combo.record_shot(ShotResult::Ufo { score: 100 }, &alien),
+            100
+        );
+        assert_eq!(combo.record_shot(ShotResult::Top, &alien), 0);
+        assert_eq!(combo.streak, 0);
+        assert_eq!(combo.multiplier(), 1);
+    }
+
+    #[test]
+    fn row_clear_bonus_is_multiplied() {
+        let mut alien = Game::new(GameConfig::default()).alien;
+        // 0番の段の0番以外を撃破済みにする
+        for live in alien.live[1..level::COLUMN_NUM].iter_mut() {
+            *live = false;
+        }
+        let mut combo = Combo::default();
+        // 段に生き残りがいれば段のボーナスはない
+        assert_eq!(combo.record_shot(alien_hit(1, 10), &alien), 0);
+        alien.live[0] = false;
+        let mut combo = Combo { streak: 5 };
+        assert_eq!(
+            combo.record_shot(alien_hit(0, 10), &alien),
+            10 + ROW_CLEAR_BONUS * 2
+        );
+    }
+
+    #[test]
+    fn perfect_bonus_requires_no_misses() {
+        let mut stats = StageStats::default();
+        assert_eq!(Combo::stage_bonus(&stats), 0);
+        stats.shots = 20;
+        stats.hits = 20;
+        assert_eq!(Combo::stage_bonus(&stats), PERFECT_BONUS);
+        stats.shots = 21;
+        assert_eq!(Combo::stage_bonus(&stats), 0);
+    }
+}
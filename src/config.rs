@@ -0,0 +1,260 @@
+use crate::combo::Scoring;
+use crate::progression::Progression;
+use crate::ruleset::Ruleset;
+use crate::storage::Storage;
+
+// 保存先のキー
+const STORAGE_KEY: &str = "invader-config";
+
+// アーケード筐体のディップスイッチに相当するゲーム設定
+#[derive(Clone)]
+pub struct GameConfig {
+    // 開始時の残機数(3～6)
+    pub start_life: i32,
+    // 残機が1つ増える得点(1000または1500)
+    pub extra_life_score: i32,
+    // タイトル画面にコイン情報を表示するならば真
+    pub show_coin_info: bool,
+    // UFOが消えてから次に出現するまでの秒数
+    pub ufo_interval: u64,
+    // 真の場合はコインを投入せずに開始できる
+    pub free_play: bool,
+    // ステージが進んだ時の難易度の上がり方
+    pub progression: Progression,
+    // エイリアンの弾とUFOの動きのルール
+    pub ruleset: Ruleset,
+    // 得点の計算方法
+    pub scoring: Scoring,
+    // ゲームパッドのスティックの遊び(傾きが何%以下なら入力しないか)
+    pub dead_zone: i32,
+    // 真の場合は発射ボタンを押し続けると弾を撃ち続ける
+    pub auto_fire: bool,
+}
+
+// 設定画面の項目
+#[derive(Clone, Copy)]
+enum ConfigItem {
+    StartLife,
+    ExtraLifeScore,
+    CoinInfo,
+    UfoInterval,
+    FreePlay,
+    Progression,
+    Ruleset,
+    Scoring,
+    DeadZone,
+    AutoFire,
+}
+const ITEMS: [ConfigItem; 10] = [
+    ConfigItem::StartLife,
+    ConfigItem::ExtraLifeScore,
+    ConfigItem::CoinInfo,
+    ConfigItem::UfoInterval,
+    ConfigItem::FreePlay,
+    ConfigItem::Progression,
+    ConfigItem::Ruleset,
+    ConfigItem::Scoring,
+    ConfigItem::DeadZone,
+    ConfigItem::AutoFire,
+];
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            start_life: 3,
+            extra_life_score: 1500,
+            show_coin_info: true,
+            ufo_interval: 25,
+            free_play: false,
+            progression: Progression::Classic,
+            ruleset: Ruleset::Standard,
+            scoring: Scoring::Classic,
+            dead_zone: 25,
+            auto_fire: true,
+        }
+    }
+}
+
+impl GameConfig {
+    // 設定画面の項目数
+    pub const ITEM_NUM: usize = ITEMS.len();
+
+    // 保存先から読み込む(保存されていない項目や不正な値は初期値にする)
+    // 1行に「項目名=値」の形式で保存されている
+    pub fn load(storage: &dyn Storage) -> Self {
+        let mut config = GameConfig::default();
+        if let Some(text) = storage.load(STORAGE_KEY) {
+            for line in text.lines() {
+                if let Some((key, value)) = line.split_once('=') {
+                    config.set_value(key.trim(), value.trim());
+                }
+            }
+        }
+        config
+    }
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let text = format!(
+            "start_life={}\nextra_life_score={}\nshow_coin_info={}\nufo_interval={}\nfree_play={}\nprogression={}\nruleset={}\nscoring={}\ndead_zone={}\nauto_fire={}",
+            self.start_life,
+            self.extra_life_score,
+            self.show_coin_info,
+            self.ufo_interval,
+            self.free_play,
+            self.progression.name(),
+            self.ruleset.name(),
+            self.scoring.name(),
+            self.dead_zone,
+            self.auto_fire,
+        );
+        storage.save(STORAGE_KEY, &text);
+    }
+    fn set_value(&mut self, key: &str, value: &str) {
+        let valid = match key {
+            "start_life" => match value.parse() {
+                Ok(life @ 3..=6) => {
+                    self.start_life = life;
+                    true
+                }
+                _ => false,
+            },
+            "extra_life_score" => match value.parse() {
+                Ok(score @ (1000 | 1500)) => {
+                    self.extra_life_score = score;
+                    true
+                }
+                _ => false,
+            },
+            "show_coin_info" => match value.parse() {
+                Ok(show) => {
+                    self.show_coin_info = show;
+                    true
+                }
+                _ => false,
+            },
+            "ufo_interval" => match value.parse() {
+                Ok(sec @ (15 | 25 | 35)) => {
+                    self.ufo_interval = sec;
+                    true
+                }
+                _ => false,
+            },
+            "free_play" => match value.parse() {
+                Ok(free_play) => {
+                    self.free_play = free_play;
+                    true
+                }
+                _ => false,
+            },
+            "progression" => match Progression::from_name(value) {
+                Some(progression) => {
+                    self.progression = progression;
+                    true
+                }
+                None => false,
+            },
+            "ruleset" => match Ruleset::from_name(value) {
+                Some(ruleset) => {
+                    self.ruleset = ruleset;
+                    true
+                }
+                None => false,
+            },
+            "scoring" => match Scoring::from_name(value) {
+                Some(scoring) => {
+                    self.scoring = scoring;
+                    true
+                }
+                None => false,
+            },
+            "dead_zone" => match value.parse() {
+                Ok(percent @ 10..=50) => {
+                    self.dead_zone = percent;
+                    true
+                }
+                _ => false,
+            },
+            "auto_fire" => match value.parse() {
+                Ok(auto_fire) => {
+                    self.auto_fire = auto_fire;
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if !valid {
+            log::info!("設定項目{}の値{}は不正なので無視します。", key, value);
+        }
+    }
+    // 設定画面に表示する項目名と値
+    pub fn item_text(&self, i: usize) -> String {
+        match ITEMS[i] {
+            ConfigItem::StartLife => format!("LIVES        {}", self.start_life),
+            ConfigItem::ExtraLifeScore => format!("BONUS LIFE   {}", self.extra_life_score),
+            ConfigItem::CoinInfo => format!("COIN INFO    {}", on_off(self.show_coin_info)),
+            ConfigItem::UfoInterval => format!("UFO INTERVAL {}", self.ufo_interval),
+            ConfigItem::FreePlay => format!("FREE PLAY    {}", on_off(self.free_play)),
+            ConfigItem::Progression => format!("STAGES       {}", self.progression.name()),
+            ConfigItem::Ruleset => format!("RULES        {}", self.ruleset.name()),
+            ConfigItem::Scoring => format!("SCORING      {}", self.scoring.name()),
+            ConfigItem::DeadZone => format!("DEAD ZONE    {}%", self.dead_zone),
+            ConfigItem::AutoFire => format!("AUTO FIRE    {}", on_off(self.auto_fire)),
+        }
+    }
+    // 設定画面で項目の値を切り替える(forwardが真なら次の値、偽なら前の値)
+    pub fn change_item(&mut self, i: usize, forward: bool) {
+        match ITEMS[i] {
+            ConfigItem::StartLife => {
+                self.start_life = cycle(&[3, 4, 5, 6], self.start_life, forward);
+            }
+            ConfigItem::ExtraLifeScore => {
+                self.extra_life_score = cycle(&[1000, 1500], self.extra_life_score, forward);
+            }
+            ConfigItem::CoinInfo => self.show_coin_info = !self.show_coin_info,
+            ConfigItem::UfoInterval => {
+                self.ufo_interval = cycle(&[15, 25, 35], self.ufo_interval, forward);
+            }
+            ConfigItem::FreePlay => self.free_play = !self.free_play,
+            ConfigItem::Progression => {
+                self.progression = cycle(
+                    &[Progression::Classic, Progression::Endless],
+                    self.progression,
+                    forward,
+                );
+            }
+            ConfigItem::Ruleset => {
+                self.ruleset = cycle(
+                    &[Ruleset::Standard, Ruleset::Accurate],
+                    self.ruleset,
+                    forward,
+                );
+            }
+            ConfigItem::Scoring => {
+                self.scoring = cycle(&[Scoring::Classic, Scoring::Modern], self.scoring, forward);
+            }
+            ConfigItem::DeadZone => {
+                self.dead_zone = cycle(&[10, 15, 20, 25, 30, 40, 50], self.dead_zone, forward);
+            }
+            ConfigItem::AutoFire => self.auto_fire = !self.auto_fire,
+        }
+    }
+}
+
+// 選択肢の中で現在の値の次(または前)の値を返す
+fn cycle<T: PartialEq + Copy>(choices: &[T], value: T, forward: bool) -> T {
+    let i = choices.iter().position(|c| *c == value).unwrap_or(0);
+    let len = choices.len();
+    if forward {
+        choices[(i + 1) % len]
+    } else {
+        choices[(i + len - 1) % len]
+    }
+}
+
+pub fn on_off(flag: bool) -> &'static str {
+    if flag {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
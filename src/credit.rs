@@ -0,0 +1,36 @@
+// 投入できるクレジットの最大値(画面には2桁で表示する)
+const MAX_CREDITS: i32 = 99;
+
+// コイン投入口とクレジット数
+pub struct Credit {
+    pub credits: i32,
+}
+
+impl Default for Credit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Credit {
+    pub fn new() -> Self {
+        Credit { credits: 0 }
+    }
+    pub fn insert_coin(&mut self) {
+        if self.credits < MAX_CREDITS {
+            self.credits += 1;
+        }
+    }
+    // 人数分のクレジットを消費して開始できれば真を返す(フリープレイ時は消費しない)
+    pub fn consume(&mut self, player_num: i32, free_play: bool) -> bool {
+        if free_play {
+            return true;
+        }
+        if player_num <= self.credits {
+            self.credits -= player_num;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -10,6 +10,12 @@ pub struct DotMap {
     pub map: Vec<Vec<u8>>,
 }
 
+impl Default for DotMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DotMap {
     pub fn new() -> Self {
         // 0クリアしたドットマップを生成
@@ -32,7 +38,7 @@ impl DotMap {
     }
     // DotMapを1ピクセル4バイトでrgbaを表し、u8のベクタにまとめる
     // top_areaとbottom_areaもまとめる
-    fn convert_to_color_bytes(
+    pub fn convert_to_color_bytes(
         &self,
         top: &Vec<Vec<u8>>,
         bottom: &Vec<Vec<u8>>,
@@ -0,0 +1,338 @@
+use crate::array_sprite::{array_sprite, array_text};
+use crate::canvas;
+use crate::dot_map::DotMap;
+use crate::level::{self, Level};
+use crate::math::Vec2;
+use crate::shield;
+use crate::sprite;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlInputElement};
+use yew::prelude::*;
+
+// シールドを置ける最大の数
+const MAX_SHIELD_NUM: usize = 8;
+// レベル名の最大文字数(プレビューの画面上部に収まる長さ)
+const MAX_NAME_LEN: usize = 26;
+
+// パレットで選ぶ道具
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tool {
+    // 引数の種類のエイリアンを置く(0: タコ, 1: カニ, 2: イカ)
+    Alien(usize),
+    // エイリアンを消して空きマスにする
+    Erase,
+    // シールドを置く、置かれたシールドをクリックすると取り除く
+    Shield,
+    // シールドの形をドット単位で塗る
+    Paint,
+}
+
+const TOOLS: [(Tool, &str); 6] = [
+    (Tool::Alien(0), "Octopus"),
+    (Tool::Alien(1), "Crab"),
+    (Tool::Alien(2), "Squid"),
+    (Tool::Erase, "Erase"),
+    (Tool::Shield, "Shield"),
+    (Tool::Paint, "Paint Shield"),
+];
+
+pub enum Msg {
+    SelectTool(Tool),
+    // プレビュー上でクリックされたドット単位の座標(ゲーム画面の左上が原点)
+    Click(Vec2),
+    SetName(String),
+    SetStartHeight(i32),
+    SetMarchSpeed(i32),
+    SetBulletSpeed(i32),
+    Import(web_sys::File),
+    Imported(Result<Level, String>),
+    Play,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    // 「Play This Level」ボタンで編集中のレベルを渡す
+    pub on_play: Callback<Level>,
+}
+
+// 隊列とシールドを編集するレベルエディタ
+// プレビューはゲームと同じくDotMapで描画する
+pub struct LevelEditor {
+    level: Level,
+    tool: Tool,
+    canvas: NodeRef,
+    // エイリアンの種類ごとのスプライト
+    alien_sprites: Vec<Vec<u8>>,
+    player_sprite: Vec<u8>,
+    char_list: Vec<Vec<u8>>,
+    // 読み込みエラーなどの表示
+    message: String,
+}
+
+impl Component for LevelEditor {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        // 難易度はステージ数によらずスライダーの値で決める
+        let mut level = Level::classic();
+        level.name = "NEW LEVEL".to_string();
+        level.start_height = Some(112);
+        level.march_speed = Some(2);
+        level.bullet_speed = Some(1);
+        LevelEditor {
+            level,
+            tool: Tool::Alien(0),
+            canvas: NodeRef::default(),
+            alien_sprites: ["octopus_open", "crab_banzai", "squid_open"]
+                .iter()
+                .map(|name| sprite::ret_dot_data(name).create_dot_map())
+                .collect(),
+            player_sprite: sprite::ret_dot_data("player").create_dot_map(),
+            char_list: sprite::char_dot_data()
+                .iter()
+                .map(|c| c.create_dot_map())
+                .collect(),
+            message: String::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::SelectTool(tool) => self.tool = tool,
+            Msg::Click(pos) => self.click(pos),
+            Msg::SetName(name) => {
+                self.level.name = name.to_uppercase().chars().take(MAX_NAME_LEN).collect()
+            }
+            Msg::SetStartHeight(height) => self.level.start_height = Some(height),
+            Msg::SetMarchSpeed(speed) => self.level.march_speed = Some(speed),
+            Msg::SetBulletSpeed(speed) => self.level.bullet_speed = Some(speed),
+            // ファイルの中身を取得してからレベルとして読み込む
+            Msg::Import(file) => ctx.link().send_future(async move {
+                let text = JsFuture::from(file.text())
+                    .await
+                    .ok()
+                    .and_then(|text| text.as_string());
+                match text {
+                    Some(text) => Msg::Imported(Level::parse(&text)),
+                    None => Msg::Imported(Err("ファイルを読み込めませんでした。".to_string())),
+                }
+            }),
+            Msg::Imported(Ok(level)) => {
+                self.message = format!("{}を読み込みました。", level.name);
+                self.level = level;
+                // 省略されていた難易度はステージ1と同じ値にする
+                self.level.start_height.get_or_insert(112);
+                self.level.march_speed.get_or_insert(2);
+                self.level.bullet_speed.get_or_insert(1);
+            }
+            Msg::Imported(Err(err)) => self.message = err,
+            Msg::Play => {
+                if self.level.formation.iter().all(|cell| cell.is_none()) {
+                    self.message = "隊列にエイリアンが1匹もいません。".to_string();
+                } else {
+                    ctx.props().on_play.emit(self.level.clone());
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let tools = TOOLS.iter().map(|(tool, label)| {
+            let tool = *tool;
+            let class = if tool == self.tool {
+                "editor-button selected"
+            } else {
+                "editor-button"
+            };
+            html! {
+                <button {class} onclick={link.callback(move |_| Msg::SelectTool(tool))}>{ *label }</button>
+            }
+        });
+        let onclick = link.callback(|e: MouseEvent| {
+            Msg::Click(Vec2::new(
+                e.offset_x() / canvas::SCALE,
+                e.offset_y() / canvas::SCALE - canvas::TOP_HEIGHT,
+            ))
+        });
+        let onimport = link.batch_callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            input
+                .files()
+                .and_then(|files| files.get(0))
+                .map(Msg::Import)
+        });
+        // 書き出すレベルファイルはデータURLとしてダウンロードさせる
+        let export_href = format!(
+            "data:text/plain;charset=utf-8,{}",
+            js_sys::encode_uri_component(&self.level.to_text())
+        );
+        let canvas_style = format!(
+            "width: {}px; height: {}px; image-rendering: pixelated;",
+            canvas::ALL_WIDTH * canvas::SCALE,
+            canvas::ALL_HEIGHT * canvas::SCALE
+        );
+        html! {
+            <div class="level-editor">
+                <h2>{ "Level Editor" }</h2>
+                <div class="editor-palette">{ for tools }</div>
+                <canvas
+                    width={canvas::ALL_WIDTH.to_string()}
+                    height={canvas::ALL_HEIGHT.to_string()}
+                    style={canvas_style}
+                    {onclick}
+                    ref={self.canvas.clone()}/>
+                <div class="editor-settings">
+                    <label>
+                        { "Name " }
+                        // ゲームのキー入力に反応させない
+                        <input type="text" value={self.level.name.clone()}
+                            onkeydown={Callback::from(|e: KeyboardEvent| e.stop_immediate_propagation())}
+                            onkeyup={Callback::from(|e: KeyboardEvent| e.stop_immediate_propagation())}
+                            oninput={link.callback(|e: InputEvent| Msg::SetName(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                    </label>
+                    { self.slider("Start Height", 56, 120, 8, self.level.start_height, link.callback(Msg::SetStartHeight)) }
+                    { self.slider("March Speed", 1, 4, 1, self.level.march_speed, link.callback(Msg::SetMarchSpeed)) }
+                    { self.slider("Bullet Speed", 1, 4, 1, self.level.bullet_speed, link.callback(Msg::SetBulletSpeed)) }
+                </div>
+                <div class="editor-files">
+                    <button class="editor-button" onclick={link.callback(|_| Msg::Play)}>{ "Play This Level" }</button>
+                    <a class="editor-button" href={export_href} download="level.txt">{ "Export" }</a>
+                    <label class="editor-button">
+                        { "Import" }
+                        <input type="file" accept=".txt" onchange={onimport}/>
+                    </label>
+                </div>
+                <p class="editor-message">{ &self.message }</p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        self.draw_preview();
+    }
+}
+
+impl LevelEditor {
+    // 範囲がmin～maxの値を選ぶスライダー
+    fn slider(
+        &self,
+        label: &str,
+        min: i32,
+        max: i32,
+        step: i32,
+        value: Option<i32>,
+        onchange: Callback<i32>,
+    ) -> Html {
+        let value = value.unwrap_or(min);
+        let oninput = Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(value) = input.value().parse() {
+                onchange.emit(value);
+            }
+        });
+        html! {
+            <label>
+                { format!("{} {} ", label, value) }
+                <input type="range" min={min.to_string()} max={max.to_string()} step={step.to_string()}
+                    value={value.to_string()} {oninput}/>
+            </label>
+        }
+    }
+    // 隊列の一番下の段のエイリアンのy座標
+    fn bottom_row_y(&self) -> i32 {
+        canvas::GAME_HEIGHT - self.level.start_height.unwrap_or(112)
+    }
+    // プレビュー上のクリックを選択中の道具で処理する
+    fn click(&mut self, pos: Vec2) {
+        match self.tool {
+            Tool::Alien(_) | Tool::Erase => {
+                // エイリアンの上下の隙間は近い方のマスとみなす
+                let dx = pos.x - level::START_POS_X;
+                let dy = self.bottom_row_y() + 12 - pos.y;
+                if dx < 0 || dy <= 0 {
+                    return;
+                }
+                let (column, row) = (
+                    (dx / level::CELL_SIZE) as usize,
+                    (dy / level::CELL_SIZE) as usize,
+                );
+                if level::COLUMN_NUM <= column || level::ROW_NUM <= row {
+                    return;
+                }
+                self.level.formation[row * level::COLUMN_NUM + column] = match self.tool {
+                    Tool::Alien(alien_type) => Some(alien_type),
+                    _ => None,
+                };
+            }
+            Tool::Shield => {
+                let x = pos.x.max(0) as usize;
+                if let Some(i) = self.shield_at(x) {
+                    self.level.shield_x.remove(i);
+                } else if self.level.shield_x.len() < MAX_SHIELD_NUM {
+                    // クリックした位置を中心に置く
+                    let max_x = canvas::GAME_WIDTH as usize - level::SHIELD_WIDTH;
+                    let x = x.saturating_sub(level::SHIELD_WIDTH / 2).min(max_x);
+                    self.level.shield_x.push(x);
+                    self.level.shield_x.sort_unstable();
+                }
+            }
+            Tool::Paint => {
+                let top = (shield::SHIELD_ROW * 8) as i32;
+                if pos.x < 0 || !(top..top + level::SHIELD_HEIGHT as i32).contains(&pos.y) {
+                    return;
+                }
+                // どのシールドをクリックしても共通の形を塗る
+                if let Some(i) = self.shield_at(pos.x as usize) {
+                    let dot_x = pos.x as usize - self.level.shield_x[i];
+                    let dot = &mut self.level.shield_map[(pos.y - top) as usize][dot_x];
+                    *dot = !*dot;
+                }
+            }
+        }
+    }
+    // x座標に重なるシールドのインデックス番号
+    fn shield_at(&self, x: usize) -> Option<usize> {
+        self.level
+            .shield_x
+            .iter()
+            .position(|sx| (*sx..sx + level::SHIELD_WIDTH).contains(&x))
+    }
+    // 編集中のレベルをゲーム開始時と同じ配置で描画する
+    fn draw_preview(&self) {
+        let canvas: HtmlCanvasElement = match self.canvas.cast() {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        let ctx: CanvasRenderingContext2d =
+            canvas.get_context("2d").unwrap().unwrap().unchecked_into();
+        let mut map = DotMap::new();
+        let mut top = vec![vec![0; canvas::TOP_WIDTH as usize]; (canvas::TOP_HEIGHT / 8) as usize];
+        let bottom =
+            vec![vec![0; canvas::BOTTOM_WIDTH as usize]; (canvas::BOTTOM_HEIGHT / 8) as usize];
+        let name: String = self.level.name.chars().take(MAX_NAME_LEN).collect();
+        array_text(&mut top, Vec2::new(8, 8), &name, &self.char_list);
+
+        map.draw_holizon_line(canvas::GAME_HEIGHT - 1);
+        shield::place(&mut map, &self.level.shield_x, &self.level.shield_sprite());
+        for (i, cell) in self.level.formation.iter().enumerate() {
+            if let Some(alien_type) = cell {
+                let pos = Vec2::new(
+                    level::START_POS_X + level::CELL_SIZE * (i % level::COLUMN_NUM) as i32,
+                    self.bottom_row_y() - level::CELL_SIZE * (i / level::COLUMN_NUM) as i32,
+                );
+                array_sprite(&mut map.map, pos, &self.alien_sprites[*alien_type]);
+            }
+        }
+        array_sprite(
+            &mut map.map,
+            Vec2::new(8, canvas::GAME_HEIGHT - 8 * 3),
+            &self.player_sprite,
+        );
+        let (imagedata, _) = map.dot_map2imagedata(&top, &bottom, false);
+        ctx.put_image_data(&imagedata, 0., 0.).unwrap();
+    }
+}
@@ -0,0 +1,353 @@
+use crate::audio::Audio;
+use crate::bottom_area::BottomArea;
+use crate::canvas;
+use crate::config::GameConfig;
+use crate::game::{Game, PlayResult};
+use crate::input::{Actions, KeyDown};
+use crate::math::Rng;
+use crate::sprite;
+use crate::top_area::TopArea;
+
+// 強化学習用の観測の形式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ObsKind {
+    // DotMap.mapの各行を上から順につなげたもの(1バイトで縦8ドット、26x224バイト)
+    DotMap,
+    // 画面全体のRGBA(縦横をscale分の1に縮小する、scale = 1で256x224x4バイト)
+    Rgba { scale: usize },
+}
+
+impl ObsKind {
+    // 観測の(高さ, 幅, チャンネル数)
+    pub fn shape(self) -> (usize, usize, usize) {
+        match self {
+            ObsKind::DotMap => (
+                (canvas::GAME_HEIGHT / 8) as usize,
+                canvas::GAME_WIDTH as usize,
+                1,
+            ),
+            ObsKind::Rgba { scale } => (
+                canvas::ALL_HEIGHT as usize / scale,
+                canvas::ALL_WIDTH as usize / scale,
+                4,
+            ),
+        }
+    }
+}
+
+// エージェントが選べる行動(左右の移動と発射の組み合わせ)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Noop,
+    Left,
+    Right,
+    Fire,
+    LeftFire,
+    RightFire,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::Noop,
+        Action::Left,
+        Action::Right,
+        Action::Fire,
+        Action::LeftFire,
+        Action::RightFire,
+    ];
+
+    // 行動の番号(ALLでの位置)から行動を返す
+    pub fn from_index(i: usize) -> Option<Self> {
+        Self::ALL.get(i).copied()
+    }
+    fn key(self) -> KeyDown {
+        KeyDown {
+            left: matches!(self, Action::Left | Action::LeftFire),
+            right: matches!(self, Action::Right | Action::RightFire),
+            shot: matches!(self, Action::Fire | Action::LeftFire | Action::RightFire),
+            ..KeyDown::default()
+        }
+    }
+}
+
+// 環境の設定
+#[derive(Clone)]
+pub struct EnvConfig {
+    pub obs: ObsKind,
+    // 1回のstepで同じ行動を続けるtick数
+    pub frame_skip: usize,
+    // 各tickで指定した行動の代わりに直前の行動を繰り返す確率
+    pub sticky_prob: f32,
+    // reset時に何もせずに進めるtick数の上限(実際のtick数はシードで決まる)
+    pub noop_max: usize,
+    // 残機が1減るごとに報酬から引く値
+    pub life_penalty: f32,
+    // この数のtickを超えたら打ち切る(Noneならゲームオーバーまで続ける)
+    pub max_ticks: Option<u64>,
+    pub game: GameConfig,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            obs: ObsKind::DotMap,
+            frame_skip: 4,
+            sticky_prob: 0.25,
+            noop_max: 30,
+            life_penalty: 100.,
+            max_ticks: Some(60 * 60 * 30),
+            game: GameConfig::default(),
+        }
+    }
+}
+
+// stepで観測と共に返す情報
+#[derive(Clone, Debug)]
+pub struct Info {
+    pub score: i32,
+    pub life: i32,
+    pub stage: usize,
+    // resetからの経過tick数
+    pub ticks: u64,
+    // ゲームオーバーではなくmax_ticksで打ち切った場合は真
+    pub truncated: bool,
+}
+
+// ブラウザなしで動かす強化学習用の環境
+// reset(seed)で新しいゲームを始め、step(action)で1行動分進める
+pub struct Env {
+    pub config: EnvConfig,
+    game: Game,
+    audio: Audio,
+    top: TopArea,
+    bottom: BottomArea,
+    rng: Rng,
+    // 直前のtickで実際に行った行動(sticky actions用)
+    pre_action: Action,
+    // 行動から読み取った入力
+    actions: Actions,
+    ticks: u64,
+    done: bool,
+}
+
+impl Env {
+    pub fn new(config: EnvConfig) -> Self {
+        if config.frame_skip == 0 {
+            panic!("frame_skipは1以上にしてください。");
+        }
+        if let ObsKind::Rgba { scale } = config.obs {
+            if scale == 0 {
+                panic!("RGBAの縮小率は1以上にしてください。");
+            }
+        }
+        let char_list: Vec<Vec<u8>> = sprite::char_dot_data()
+            .iter()
+            .map(|n| n.create_dot_map())
+            .collect();
+        let player_sprite = sprite::ret_dot_data("player").create_dot_map();
+        Env {
+            game: Game::new(config.game.clone()),
+            audio: Audio::silent(),
+            top: TopArea::new(char_list.clone()),
+            bottom: BottomArea::new(char_list, player_sprite),
+            rng: Rng::new(0),
+            pre_action: Action::Noop,
+            actions: Actions::default(),
+            ticks: 0,
+            done: true,
+            config,
+        }
+    }
+    // 新しいゲームを始めて(最初の観測, 情報)を返す
+    pub fn reset(&mut self, seed: u64) -> (Vec<u8>, Info) {
+        self.rng = Rng::new(seed);
+        self.game = Game::new(self.config.game.clone());
+        self.game.start(1);
+        self.game.reset_stage(None, None);
+        self.top.all_clear();
+        self.bottom.all_clear();
+        self.pre_action = Action::Noop;
+        self.actions = Actions::default();
+        self.ticks = 0;
+        self.done = false;
+        // 毎回同じ状態から始まらないように、シードで決めたtick数だけ何もしない
+        let noop_num = self.rng.next_u64() % (self.config.noop_max as u64 + 1);
+        for _ in 0..noop_num {
+            self.tick(Action::Noop);
+        }
+        (self.observation(), self.info(false))
+    }
+    // frame_skipのtick数だけ行動を続け、(観測, 報酬, 終了したか, 情報)を返す
+    // 終了した後はresetするまで報酬0で同じ観測を返す
+    pub fn step(&mut self, action: Action) -> (Vec<u8>, f32, bool, Info) {
+        let mut reward = 0.;
+        let mut truncated = false;
+        for _ in 0..self.config.frame_skip {
+            if self.done {
+                break;
+            }
+            // 一定の確率で直前の行動を繰り返す
+            let action = if self.rng.next_f32() < self.config.sticky_prob {
+                self.pre_action
+            } else {
+                action
+            };
+            let pre_score = self.game.player_bullet.score;
+            let pre_life = self.game.player.life;
+            let result = self.tick(action);
+            reward += (self.game.player_bullet.score - pre_score) as f32;
+            if self.game.player.life < pre_life {
+                reward -= self.config.life_penalty * (pre_life - self.game.player.life) as f32;
+            }
+            if result == PlayResult::GameOver {
+                self.done = true;
+            } else if matches!(self.config.max_ticks, Some(max) if max <= self.ticks) {
+                self.done = true;
+                truncated = true;
+            }
+        }
+        let info = self.info(truncated);
+        (self.observation(), reward, self.done, info)
+    }
+    // 現在の観測
+    pub fn observation(&mut self) -> Vec<u8> {
+        match self.config.obs {
+            ObsKind::DotMap => self.game.map.map.concat(),
+            ObsKind::Rgba { scale } => {
+                // ブラウザと同じように画面上部と下部も描く
+                self.top
+                    .draw_score([self.game.player_bullet.score, 0], 0, 0, false);
+                self.top.draw_stage(Some(self.game.stage));
+                self.bottom.draw(self.game.player.life, None);
+                let rgba = self.game.map.convert_to_color_bytes(
+                    &self.top.top,
+                    &self.bottom.bottom,
+                    self.game.player_exploding,
+                );
+                downscale(&rgba, scale)
+            }
+        }
+    }
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+    fn info(&self, truncated: bool) -> Info {
+        Info {
+            score: self.game.player_bullet.score,
+            life: self.game.player.life,
+            stage: self.game.stage,
+            ticks: self.ticks,
+            truncated,
+        }
+    }
+    // 1tick進める(エイリアンを全滅させたら次のステージを始める)
+    fn tick(&mut self, action: Action) -> PlayResult {
+        self.pre_action = action;
+        self.ticks += 1;
+        self.actions.sample(&action.key());
+        let result = self.game.update(&self.actions, &self.audio);
+        if result == PlayResult::StageCleared {
+            self.game.next_stage();
+            self.game.reset_stage(None, None);
+        }
+        result
+    }
+}
+
+// 画面全体のRGBAを縦横scale分の1に縮小する(各ブロックの平均色)
+fn downscale(rgba: &[u8], scale: usize) -> Vec<u8> {
+    if scale == 1 {
+        return rgba.to_vec();
+    }
+    let width = canvas::ALL_WIDTH as usize;
+    let (height, out_width, _) = ObsKind::Rgba { scale }.shape();
+    let mut out = Vec::with_capacity(height * out_width * 4);
+    for y in 0..height {
+        for x in 0..out_width {
+            for c in 0..4 {
+                let mut sum = 0;
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        sum += rgba[((y * scale + dy) * width + x * scale + dx) * 4 + c] as usize;
+                    }
+                }
+                out.push((sum / (scale * scale)) as u8);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 行動をシードから決めた順に選んで、(観測, 報酬, 終了したか, 得点, tick数)の列を返す
+    fn run(seed: u64, steps: usize) -> Vec<(Vec<u8>, f32, bool, i32, u64)> {
+        let mut env = Env::new(EnvConfig::default());
+        let (obs, info) = env.reset(seed);
+        let mut rng = Rng::new(seed + 1);
+        let mut results = vec![(obs, 0., false, info.score, info.ticks)];
+        for _ in 0..steps {
+            let action = Action::ALL[(rng.next_u64() % Action::ALL.len() as u64) as usize];
+            let (obs, reward, done, info) = env.step(action);
+            results.push((obs, reward, done, info.score, info.ticks));
+        }
+        results
+    }
+
+    #[test]
+    fn same_seed_gives_same_episode() {
+        assert_eq!(run(7, 300), run(7, 300));
+        assert_ne!(run(7, 300), run(8, 300));
+    }
+
+    #[test]
+    fn reset_reports_noop_ticks() {
+        let mut env = Env::new(EnvConfig::default());
+        let (obs, info) = env.reset(3);
+        assert_eq!(obs.len(), 26 * 224);
+        assert_eq!(info.ticks, env.ticks);
+        assert!(info.ticks <= env.config.noop_max as u64);
+        assert_eq!(info.life, env.game().player.life);
+        assert!(!info.truncated);
+    }
+
+    #[test]
+    fn downscale_output_size() {
+        let rgba = vec![100; canvas::ALL_HEIGHT as usize * canvas::ALL_WIDTH as usize * 4];
+        assert_eq!(downscale(&rgba, 1).len(), rgba.len());
+        for scale in [2, 3, 4] {
+            let out = downscale(&rgba, scale);
+            let (height, width, channels) = ObsKind::Rgba { scale }.shape();
+            assert_eq!(out.len(), height * width * channels);
+            // 一様な画像は縮小しても同じ色
+            assert!(out.iter().all(|c| *c == 100));
+        }
+        assert_eq!(ObsKind::Rgba { scale: 2 }.shape(), (128, 112, 4));
+    }
+
+    #[test]
+    fn life_loss_is_penalized_once() {
+        let config = EnvConfig {
+            frame_skip: 1,
+            sticky_prob: 0.,
+            life_penalty: 100.,
+            ..EnvConfig::default()
+        };
+        let mut env = Env::new(config);
+        env.reset(0);
+        let start_life = env.game().player.life;
+        // プレイヤーを撃破させる(撃たないので得点は増えない)
+        env.game.player.remove(&mut env.game.map, &env.audio);
+        let mut rewards = Vec::new();
+        while env.game().player.life == start_life {
+            let (_, reward, _, info) = env.step(Action::Noop);
+            assert_eq!(info.score, 0);
+            rewards.push(reward);
+        }
+        assert_eq!(env.game().player.life, start_life - 1);
+        assert_eq!(rewards.pop(), Some(-100.));
+        assert!(rewards.iter().all(|r| *r == 0.));
+    }
+}
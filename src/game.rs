@@ -0,0 +1,413 @@
+use crate::alien::{self, Alien, Formation};
+use crate::audio::Audio;
+use crate::canvas;
+use crate::combo::{Combo, Scoring};
+use crate::config::GameConfig;
+use crate::dot_map::DotMap;
+use crate::game_mode::{GameMode, WaveClear};
+use crate::input::Actions;
+use crate::key_binding::Control;
+use crate::level::Level;
+use crate::player::{Bullet, Player};
+use crate::progression::StageParams;
+use crate::shield;
+use crate::sprite;
+use crate::stats::StageStats;
+use crate::ufo::Ufo;
+
+// プレイ中の1tickの結果
+#[derive(PartialEq)]
+pub enum PlayResult {
+    Playing,
+    // プレイヤーが撃破されて残機が減った
+    LifeLost,
+    // エイリアンを全滅させて次のステージへ進む
+    StageCleared,
+    GameOver,
+}
+
+// 画面やキー入力に依存しないゲーム本体
+// ブラウザではGameCanvasが、ヘッドレス実行ではexamplesのプログラムが毎tick更新する
+pub struct Game {
+    pub map: DotMap,
+    pub player: Player,
+    pub player_bullet: Bullet,
+    pub alien: Alien,
+    pub alien_bullets: alien::BulletManage,
+    pub ufo: Ufo,
+    pub stage: usize, // 最初は1、以降は周回しても増え続ける
+    pub config: GameConfig,
+    // 遊んでいる(最後に遊んだ)ルール
+    pub mode: GameMode,
+    // キャンペーンで順に遊ぶレベル
+    pub campaign: Vec<Level>,
+    // キャンペーン以外で遊ぶアーケード版と同じレベル
+    pub classic_level: Level,
+    // 真の場合、画面全体を赤色にする
+    pub player_exploding: bool,
+    // 遊んでいるステージの成績
+    pub stats: StageStats,
+    // このゲームで終えたステージの成績
+    pub history: Vec<StageStats>,
+    // モダンの得点方式での連続命中の状態
+    pub combo: Combo,
+    // エイリアンを全滅させたtickのみ真
+    pub wave_cleared: bool,
+    // 最後の1匹を最高速度で倒したtickのみ真
+    pub top_speed_kill: bool,
+}
+
+impl Game {
+    pub fn new(config: GameConfig) -> Self {
+        // スプライトデータ取得
+        let player_data = sprite::ret_dot_data("player");
+        let bullet_player_data = sprite::ret_dot_data("bullet_player");
+        if bullet_player_data.width != 1 {
+            panic!("プレイヤーの弾の幅は1以外は不正です。");
+        }
+        let player_explosion_1_data = sprite::ret_dot_data("player_explosion_1");
+        let player_explosion_2_data = sprite::ret_dot_data("player_explosion_2");
+        let player_bullet_explosion_data = sprite::ret_dot_data("player_bullet_explosion");
+        let ufo_data = sprite::ret_dot_data("ufo");
+        let ufo_explosion_data = sprite::ret_dot_data("ufo_explosion");
+        let octopus_open_data = sprite::ret_dot_data("octopus_open");
+        let octopus_close_data = sprite::ret_dot_data("octopus_close");
+        let crab_banzai_data = sprite::ret_dot_data("crab_banzai");
+        let crab_down_data = sprite::ret_dot_data("crab_down");
+        let squid_open_data = sprite::ret_dot_data("squid_open");
+        let squid_close_data = sprite::ret_dot_data("squid_close");
+        let alien_explosion_data = sprite::ret_dot_data("alien_explosion");
+        let alien_bullet_explosion_data = sprite::ret_dot_data("alien_bullet_explosion");
+        let num_data = sprite::char_dot_data();
+
+        // 各構造体初期化
+        let char_list: Vec<Vec<u8>> = num_data.iter().map(|n| n.create_dot_map()).collect();
+        let player = Player::new(
+            player_data.create_dot_map(),
+            player_explosion_1_data.create_dot_map(),
+            player_explosion_2_data.create_dot_map(),
+        );
+        let player_bullet = Bullet::new(
+            bullet_player_data.create_dot_map(),
+            player_bullet_explosion_data.create_dot_map(),
+        );
+        let ufo = Ufo::new(
+            ufo_data.create_dot_map(),
+            ufo_explosion_data.create_dot_map(),
+            char_list,
+        );
+        let alien = Alien::new(
+            octopus_open_data.create_dot_map(),
+            octopus_close_data.create_dot_map(),
+            crab_banzai_data.create_dot_map(),
+            crab_down_data.create_dot_map(),
+            squid_open_data.create_dot_map(),
+            squid_close_data.create_dot_map(),
+            alien_explosion_data.create_dot_map(),
+        );
+        let alien_bullets = alien::BulletManage::new(alien_bullet_explosion_data.create_dot_map());
+
+        let mut game = Game {
+            map: DotMap::new(),
+            player,
+            player_bullet,
+            alien,
+            alien_bullets,
+            ufo,
+            stage: 1,
+            config,
+            mode: GameMode::Classic,
+            campaign: vec![Level::classic()],
+            classic_level: Level::classic(),
+            player_exploding: false,
+            stats: StageStats::default(),
+            history: Vec::new(),
+            combo: Combo::default(),
+            wave_cleared: false,
+            top_speed_kill: false,
+        };
+        // エイリアンの初期化
+        let params = game.stage_params();
+        game.alien.reset(&params, &game.classic_level.formation);
+        // プレイヤーの初期化
+        game.player.reset_all(&game.config);
+        game.ufo.reset();
+        game
+    }
+    // ゲーム開始時に呼び出す
+    pub fn start(&mut self, stage: usize) {
+        self.stage = stage;
+        self.player.reset_all(&self.config);
+        self.player_bullet.reset_all();
+        self.player_exploding = false;
+        self.stats = StageStats::default();
+        self.history.clear();
+        self.combo = Combo::default();
+    }
+    // ステージを進める(次にreset_stageを呼び出すと開始する)
+    pub fn next_stage(&mut self) {
+        self.stage += 1;
+        self.player.reset_stage();
+        self.player_bullet.reset_stage();
+    }
+    // 画面を作り直してステージを開始する
+    // 交代前に削られたシールドや生き残っていた隊列があれば再開する
+    pub fn reset_stage(&mut self, shield_rows: Option<Vec<Vec<u8>>>, formation: Option<Formation>) {
+        // すべて消す
+        self.map.all_clear();
+        // プレイヤーの下の横線
+        self.map.draw_holizon_line(canvas::GAME_HEIGHT - 1);
+        // ステージ数とレベルに応じた難易度
+        let params = self.stage_params();
+        let level = self.stage_level().clone();
+        match shield_rows {
+            Some(rows) => shield::restore_rows(&mut self.map, rows),
+            // シールド配置
+            None => shield::place(&mut self.map, &level.shield_x, &level.shield_sprite()),
+        }
+        match formation {
+            Some(formation) => self.alien.restore_formation(&formation, &params),
+            None => self.alien.reset(&params, &level.formation),
+        }
+        self.alien_bullets.reset(&params);
+        self.alien_bullets
+            .set_shot_tables(&level.plunger_columns, &level.squiggly_columns);
+        self.alien_bullets.set_ruleset(self.config.ruleset);
+        self.alien_bullets
+            .set_shot_types(self.mode.practice().map_or([true; 3], |p| p.shot_types));
+        self.ufo.reset();
+    }
+    // プレイ中の1tick分の更新
+    // actionsはtickごとに1回読み取った入力
+    pub fn update(&mut self, actions: &Actions, audio: &Audio) -> PlayResult {
+        let mut result = PlayResult::Playing;
+        self.player_bullet.shot_result = None;
+        self.wave_cleared = false;
+        self.top_speed_kill = false;
+        let pre_fire_cnt = self.player_bullet.fire_cnt;
+        let was_exploding = self.player.explosion_cnt.is_some();
+        // 練習モードではUFOを出さないこともできる
+        if !matches!(self.mode.practice(), Some(p) if !p.ufo) {
+            self.ufo.update(
+                &mut self.map,
+                self.player_bullet.fire_cnt,
+                self.alien.live_num,
+                self.alien_bullets.squiggly_live(),
+                audio,
+                &self.config,
+            );
+        }
+
+        self.alien
+            .update(&mut self.map, self.player_exploding, audio);
+        self.alien_bullets.update(
+            &mut self.map,
+            &mut self.player,
+            &mut self.player_bullet,
+            &self.alien,
+            self.ufo.live,
+            audio,
+        );
+
+        let pre_life = self.player.life;
+        self.player.update(&mut self.map, actions);
+        // 練習モードで残機無限の場合は残機を減らさない
+        if matches!(self.mode.practice(), Some(p) if p.infinite_life) {
+            self.player.life = pre_life;
+        }
+        if self.player.life < pre_life {
+            result = PlayResult::LifeLost;
+        }
+        // 連射しない設定では、発射ボタンを押し直さないと次の弾を撃てない
+        let fire = if self.config.auto_fire {
+            actions.held(Control::Shot)
+        } else {
+            actions.just_pressed(Control::Shot)
+        };
+        self.player_bullet.update(
+            &mut self.map,
+            &mut self.player,
+            &mut self.ufo,
+            &mut self.alien,
+            fire,
+            audio,
+        );
+        self.alien_bullets
+            .collide_player_bullet(&mut self.map, &mut self.player_bullet);
+
+        // 成績を集計する
+        self.stats.ticks += 1;
+        self.stats.shots += self.player_bullet.fire_cnt - pre_fire_cnt;
+        if let Some(shot_result) = self.player_bullet.shot_result {
+            self.stats.record_shot(shot_result);
+            // モダンの得点方式では倍率とボーナスの点数を加える
+            if self.config.scoring == Scoring::Modern {
+                let bonus = self.combo.record_shot(shot_result, &self.alien);
+                self.player_bullet.score += bonus;
+                self.stats.bonus_points += bonus;
+            }
+        }
+        // モダンの得点方式では1発も外さずに全滅させたらボーナスを加える
+        if self.alien.live_num <= 0 && self.config.scoring == Scoring::Modern {
+            let bonus = Combo::stage_bonus(&self.stats);
+            self.player_bullet.score += bonus;
+            self.stats.bonus_points += bonus;
+        }
+        self.player
+            .check_extra_life(self.player_bullet.score, &self.config);
+
+        // 制限時間、経過時間を進める
+        let time_up = self.mode.tick();
+        // エイリアンが全滅したら
+        if self.alien.live_num <= 0 {
+            self.wave_cleared = true;
+            self.top_speed_kill = self.alien.top_speed();
+            match self.mode.wave_cleared() {
+                // 次のステージへ進む
+                WaveClear::NextStage => result = PlayResult::StageCleared,
+                // 間を置かずに次の隊列を出現させる
+                WaveClear::Respawn => {
+                    self.finish_stats();
+                    self.stage += 1;
+                    let params = self.stage_params();
+                    let formation = self.stage_level().formation.clone();
+                    self.alien.reset(&params, &formation);
+                    self.alien_bullets.set_params(&params);
+                }
+                WaveClear::Finish => {
+                    result = PlayResult::GameOver;
+                    self.ufo.reset();
+                }
+            }
+        }
+        // プレイヤーの残機が0、エイリアンがプレイヤーの高さまで侵攻した、または時間切れになったら
+        if self.player.life <= 0 || self.alien.invaded() || time_up {
+            // ゲームオーバー
+            result = PlayResult::GameOver;
+            // 音を止める
+            self.ufo.reset();
+            if self.alien.invaded() {
+                // プレイヤーの高さに降りてきた個体を描く
+                self.alien
+                    .update(&mut self.map, self.player_exploding, audio);
+                // エイリアンに侵攻されていたら爆発を起こす
+                self.player.remove(&mut self.map, audio);
+            };
+        }
+        // プレイヤーが爆発中は画面全体を赤にする
+        self.player_exploding = self.player.explosion_cnt.is_some();
+
+        if !was_exploding && self.player_exploding {
+            self.stats.lives_lost += 1;
+        }
+        if result == PlayResult::StageCleared || result == PlayResult::GameOver {
+            self.finish_stats();
+        }
+        result
+    }
+    // 遊んでいるステージの成績を終えたステージの成績に加える
+    fn finish_stats(&mut self) {
+        self.history.push(std::mem::take(&mut self.stats));
+    }
+    // 現在のステージで遊ぶレベル(キャンペーンはステージ数の順、それ以外はアーケード版と同じ)
+    pub fn stage_level(&self) -> &Level {
+        match self.mode {
+            GameMode::Campaign { .. } => {
+                &self.campaign[(self.stage.max(1) - 1).min(self.campaign.len() - 1)]
+            }
+            GameMode::Custom { ref level, .. } => level,
+            _ => &self.classic_level,
+        }
+    }
+    // ステージ数による難易度をレベルの設定で上書きしたもの
+    pub fn stage_params(&self) -> StageParams {
+        self.stage_level()
+            .apply(self.config.progression.params(self.stage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec2;
+
+    // 発射ボタンを押し続け、弾が消えて次の弾を撃てるようになるまで進める
+    fn hold_until_reloaded(game: &mut Game, actions: &mut Actions, audio: &Audio) {
+        actions.sample_controls(&[Control::Shot]);
+        game.update(actions, audio);
+        assert!(game.player_bullet.shot_pos().is_some());
+        while game.player_bullet.shot_pos().is_some() {
+            actions.sample_controls(&[Control::Shot]);
+            game.update(actions, audio);
+        }
+        // 弾が消えた後の射撃禁止の時間を待つ
+        for _ in 0..20 {
+            actions.sample_controls(&[Control::Shot]);
+            game.update(actions, audio);
+        }
+    }
+
+    fn new_game(auto_fire: bool) -> Game {
+        Game::new(GameConfig {
+            auto_fire,
+            ..GameConfig::default()
+        })
+    }
+
+    #[test]
+    fn update_counts_shots_ticks_and_lives_lost() {
+        let audio = Audio::silent();
+        let mut game = new_game(true);
+        let mut actions = Actions::default();
+        for _ in 0..10 {
+            actions.sample_controls(&[]);
+            game.update(&actions, &audio);
+        }
+        assert_eq!(game.stats.ticks, 10);
+        assert_eq!(game.stats.shots, 0);
+        hold_until_reloaded(&mut game, &mut actions, &audio);
+        assert!(game.stats.shots > 1);
+        assert_eq!(game.stats.shots, game.player_bullet.fire_cnt);
+
+        // 撃破された時に1回だけ数える(爆発中のtickでは数えない)
+        assert!(game.player.explosion_cnt.is_none());
+        let lives_lost = game.stats.lives_lost;
+        // プレイヤーのすぐ上にエイリアンの弾を置く
+        let player_pos = game.player.pos;
+        let bullet = &mut game.alien_bullets.bullets[0];
+        bullet.pos = Vec2::new(player_pos.x + 7, player_pos.y - 8);
+        bullet.speed = 4;
+        bullet.live = true;
+        for _ in 0..20 {
+            actions.sample_controls(&[]);
+            game.update(&actions, &audio);
+        }
+        assert_eq!(game.stats.lives_lost, lives_lost + 1);
+    }
+
+    #[test]
+    fn auto_fire_keeps_firing_while_held() {
+        let audio = Audio::silent();
+        let mut game = new_game(true);
+        let mut actions = Actions::default();
+        hold_until_reloaded(&mut game, &mut actions, &audio);
+        assert!(game.player_bullet.fire_cnt > 1);
+    }
+
+    #[test]
+    fn manual_fire_requires_re_press() {
+        let audio = Audio::silent();
+        let mut game = new_game(false);
+        let mut actions = Actions::default();
+        hold_until_reloaded(&mut game, &mut actions, &audio);
+        // 押し続けても次の弾は撃たない
+        assert_eq!(game.player_bullet.fire_cnt, 1);
+        assert!(game.player.explosion_cnt.is_none());
+        // 離してから押し直すと次の弾を撃てる
+        actions.sample_controls(&[]);
+        game.update(&actions, &audio);
+        actions.sample_controls(&[Control::Shot]);
+        game.update(&actions, &audio);
+        assert_eq!(game.player_bullet.fire_cnt, 2);
+    }
+}
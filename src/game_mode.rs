@@ -0,0 +1,236 @@
+use crate::level::Level;
+use crate::practice::Practice;
+
+// 1秒あたりのtick数(描画の更新回数)
+pub const TICKS_PER_SEC: i32 = 60;
+// スコアアタックの制限時間(2分)
+const SCORE_ATTACK_TICKS: i32 = 120 * TICKS_PER_SEC;
+// タイムアタックでクリアするステージ数
+const TIME_ATTACK_STAGES: usize = 3;
+
+// タイトル画面で選ぶルール(ルールごとにハイスコア表を持つ)
+#[derive(Clone, Copy, PartialEq)]
+pub enum ModeKind {
+    Classic,
+    ScoreAttack,
+    TimeAttack,
+    Campaign,
+}
+
+impl ModeKind {
+    pub const ALL: [ModeKind; 4] = [
+        ModeKind::Classic,
+        ModeKind::ScoreAttack,
+        ModeKind::TimeAttack,
+        ModeKind::Campaign,
+    ];
+
+    pub fn index(self) -> usize {
+        match self {
+            ModeKind::Classic => 0,
+            ModeKind::ScoreAttack => 1,
+            ModeKind::TimeAttack => 2,
+            ModeKind::Campaign => 3,
+        }
+    }
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            ModeKind::Classic => "CLASSIC",
+            ModeKind::ScoreAttack => "SCORE ATTACK",
+            ModeKind::TimeAttack => "TIME ATTACK",
+            ModeKind::Campaign => "CAMPAIGN",
+        }
+    }
+    // ハイスコア表の保存先のキー
+    pub fn storage_key(self) -> &'static str {
+        match self {
+            ModeKind::Classic => "invader-high-score",
+            ModeKind::ScoreAttack => "invader-high-score-score-attack",
+            ModeKind::TimeAttack => "invader-high-score-time-attack",
+            ModeKind::Campaign => "invader-high-score-campaign",
+        }
+    }
+    // ハイスコア表の値が小さいほど上位ならば真(タイムアタックはクリアタイム)
+    pub fn lower_is_better(self) -> bool {
+        self == ModeKind::TimeAttack
+    }
+    // ハイスコア表の値を表示用の文字列にする
+    pub fn format_value(self, value: i32) -> String {
+        match self {
+            ModeKind::TimeAttack => format_time(value),
+            _ => format!("{:>5}", value),
+        }
+    }
+}
+
+// tick数を「分:秒.1/100秒」の文字列にする(9:59.99で止める)
+pub fn format_time(ticks: i32) -> String {
+    let centi = (ticks * 100 / TICKS_PER_SEC).min(59999);
+    format!(
+        "{}:{:02}.{:02}",
+        centi / 6000,
+        centi / 100 % 60,
+        centi % 100
+    )
+}
+
+// 遊んでいるルールとその進行状況
+pub enum GameMode {
+    Classic,
+    Practice(Practice),
+    // 残りtick数
+    ScoreAttack { remaining: i32 },
+    // 開始からの経過tick数と、各ステージをクリアした時の経過tick数
+    TimeAttack { elapsed: i32, splits: Vec<i32> },
+    // キャンペーンのレベル数と、クリアしたレベル数
+    Campaign { level_num: usize, cleared: usize },
+    // レベルエディタで作ったレベルを試しに遊ぶ(クリアしたら真)
+    Custom { level: Level, cleared: bool },
+}
+
+// エイリアンを全滅させた後の進み方
+pub enum WaveClear {
+    // 通常通り間を置いて次のステージへ進む
+    NextStage,
+    // 間を置かずに次の隊列を出現させる
+    Respawn,
+    // ゲームを終える
+    Finish,
+}
+
+impl GameMode {
+    // level_numはキャンペーンのレベル数(キャンペーン以外では使わない)
+    pub fn new(kind: ModeKind, level_num: usize) -> Self {
+        match kind {
+            ModeKind::Classic => GameMode::Classic,
+            ModeKind::ScoreAttack => GameMode::ScoreAttack {
+                remaining: SCORE_ATTACK_TICKS,
+            },
+            ModeKind::TimeAttack => GameMode::TimeAttack {
+                elapsed: 0,
+                splits: Vec::new(),
+            },
+            ModeKind::Campaign => GameMode::Campaign {
+                level_num,
+                cleared: 0,
+            },
+        }
+    }
+    // 記録するハイスコア表の種類(練習モードは記録しないのでNone)
+    pub fn kind(&self) -> Option<ModeKind> {
+        match self {
+            GameMode::Classic => Some(ModeKind::Classic),
+            GameMode::Practice(_) | GameMode::Custom { .. } => None,
+            GameMode::ScoreAttack { .. } => Some(ModeKind::ScoreAttack),
+            GameMode::TimeAttack { .. } => Some(ModeKind::TimeAttack),
+            GameMode::Campaign { .. } => Some(ModeKind::Campaign),
+        }
+    }
+    pub fn practice(&self) -> Option<&Practice> {
+        match self {
+            GameMode::Practice(practice) => Some(practice),
+            _ => None,
+        }
+    }
+    // プレイ中に毎tick呼び出し、時間切れになったら真を返す
+    pub fn tick(&mut self) -> bool {
+        match self {
+            GameMode::ScoreAttack { remaining } => {
+                *remaining -= 1;
+                *remaining <= 0
+            }
+            GameMode::TimeAttack { elapsed, .. } => {
+                *elapsed += 1;
+                false
+            }
+            _ => false,
+        }
+    }
+    // エイリアンを全滅させた時に呼び出し、次にどうするかを返す
+    pub fn wave_cleared(&mut self) -> WaveClear {
+        match self {
+            GameMode::ScoreAttack { .. } => WaveClear::Respawn,
+            GameMode::TimeAttack { elapsed, splits } => {
+                splits.push(*elapsed);
+                if TIME_ATTACK_STAGES <= splits.len() {
+                    WaveClear::Finish
+                } else {
+                    WaveClear::NextStage
+                }
+            }
+            GameMode::Campaign { level_num, cleared } => {
+                *cleared += 1;
+                if *level_num <= *cleared {
+                    WaveClear::Finish
+                } else {
+                    WaveClear::NextStage
+                }
+            }
+            GameMode::Custom { cleared, .. } => {
+                *cleared = true;
+                WaveClear::Finish
+            }
+            _ => WaveClear::NextStage,
+        }
+    }
+    // 目標を達成してゲームを終えたならば真
+    pub fn is_cleared(&self) -> bool {
+        match self {
+            GameMode::TimeAttack { splits, .. } => TIME_ATTACK_STAGES <= splits.len(),
+            GameMode::Campaign { level_num, cleared } => level_num <= cleared,
+            GameMode::Custom { cleared, .. } => *cleared,
+            _ => false,
+        }
+    }
+    // ハイスコア表に載せる値(載せない場合はNone)
+    pub fn result(&self, score: i32) -> Option<i32> {
+        match self {
+            GameMode::Practice(_) | GameMode::Custom { .. } => None,
+            // タイムアタックはクリアした場合のみクリアタイムを載せる
+            GameMode::TimeAttack { elapsed, .. } => {
+                if self.is_cleared() {
+                    Some(*elapsed)
+                } else {
+                    None
+                }
+            }
+            _ => Some(score),
+        }
+    }
+    // 画面上部に表示するタイマー(9文字以内)
+    pub fn timer_text(&self) -> Option<String> {
+        match self {
+            GameMode::ScoreAttack { remaining } => {
+                let sec = ((*remaining).max(0) + TICKS_PER_SEC - 1) / TICKS_PER_SEC;
+                Some(format!("TIME {}:{:02}", sec / 60, sec % 60))
+            }
+            GameMode::TimeAttack { elapsed, .. } => Some(format!("{:>9}", format_time(*elapsed))),
+            _ => None,
+        }
+    }
+    // 直前にクリアしたステージのスプリットタイム
+    pub fn last_split_text(&self) -> Option<String> {
+        match self {
+            GameMode::TimeAttack { splits, .. } => splits
+                .last()
+                .map(|split| format!("SPLIT {}", format_time(*split))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_is_score_except_practice_and_time_attack() {
+        assert_eq!(GameMode::Classic.result(1500), Some(1500));
+        let score_attack = GameMode::ScoreAttack { remaining: 0 };
+        assert_eq!(score_attack.result(1500), Some(1500));
+        assert_eq!(GameMode::Practice(Practice::default()).result(1500), None);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::input::KeyDown;
+use crate::listener::Listener;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{window, Gamepad, GamepadButton, GamepadEvent};
+
+// Standard Gamepadの配置でのボタン番号
+const BUTTON_A: u32 = 0; // 下の面ボタン
+const BUTTON_SELECT: u32 = 8;
+const BUTTON_START: u32 = 9;
+const BUTTON_UP: u32 = 12;
+const BUTTON_DOWN: u32 = 13;
+const BUTTON_LEFT: u32 = 14;
+const BUTTON_RIGHT: u32 = 15;
+// 左スティックの軸番号
+const AXIS_X: u32 = 0;
+const AXIS_Y: u32 = 1;
+
+// Gamepad APIで毎フレームゲームパッドの状態を読み取る
+// USB接続のアーケードスティックもゲームパッドとして扱える
+#[derive(Default)]
+pub struct GamepadInput {
+    // 接続中のゲームパッドの番号(接続、切断のイベントで更新する)
+    connected: Rc<RefCell<Vec<u32>>>,
+    listeners: Vec<Listener>,
+}
+
+impl GamepadInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // ゲームパッドの接続、切断を検出する(登録済みならば何もしない)
+    pub fn setup(&mut self) {
+        if !self.listeners.is_empty() {
+            return;
+        }
+        let window = window().unwrap();
+
+        let connected = Rc::clone(&self.connected);
+        self.listeners.push(Listener::new(
+            &window,
+            "gamepadconnected",
+            move |e: GamepadEvent| {
+                if let Some(gamepad) = e.gamepad() {
+                    log::info!(
+                        "ゲームパッド{}が接続されました。 {}",
+                        gamepad.index(),
+                        gamepad.id()
+                    );
+                    connected.borrow_mut().push(gamepad.index());
+                }
+            },
+        ));
+
+        let connected = Rc::clone(&self.connected);
+        self.listeners.push(Listener::new(
+            &window,
+            "gamepaddisconnected",
+            move |e: GamepadEvent| {
+                if let Some(gamepad) = e.gamepad() {
+                    log::info!("ゲームパッド{}が切断されました。", gamepad.index());
+                    connected.borrow_mut().retain(|i| *i != gamepad.index());
+                }
+            },
+        ));
+    }
+    // イベントリスナーの登録を解除する
+    pub fn teardown(&mut self) {
+        self.listeners.clear();
+    }
+    // 接続中のゲームパッドの入力をinput_keyに加える(キーボードで押されているキーはそのまま)
+    // D-padまたは左スティックで移動とカーソル移動、Aボタンでショット、Startでポーズ、Selectで1人プレイ開始
+    // スティックの傾きがdead_zone(0～1)以下の場合は入力しない
+    pub fn poll(&self, input_key: &mut KeyDown, dead_zone: f64) {
+        if self.connected.borrow().is_empty() {
+            return;
+        }
+        let gamepads = match window().and_then(|w| w.navigator().get_gamepads().ok()) {
+            Some(gamepads) => gamepads,
+            None => return,
+        };
+        for gamepad in gamepads.iter() {
+            // 切断されたゲームパッドはnullになる
+            let gamepad = match gamepad.dyn_into::<Gamepad>() {
+                Ok(gamepad) if gamepad.connected() => gamepad,
+                _ => continue,
+            };
+            let buttons = gamepad.buttons();
+            let pressed = |i: u32| {
+                buttons
+                    .get(i)
+                    .dyn_into::<GamepadButton>()
+                    .is_ok_and(|button| button.pressed())
+            };
+            let axes = gamepad.axes();
+            let x = axes.get(AXIS_X).as_f64().unwrap_or(0.);
+            let y = axes.get(AXIS_Y).as_f64().unwrap_or(0.);
+
+            input_key.left |= pressed(BUTTON_LEFT) || x < -dead_zone;
+            input_key.right |= pressed(BUTTON_RIGHT) || dead_zone < x;
+            input_key.up |= pressed(BUTTON_UP) || y < -dead_zone;
+            input_key.down |= pressed(BUTTON_DOWN) || dead_zone < y;
+            input_key.shot |= pressed(BUTTON_A);
+            input_key.pause |= pressed(BUTTON_START);
+            input_key.start1 |= pressed(BUTTON_SELECT);
+        }
+    }
+}
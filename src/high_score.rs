@@ -0,0 +1,478 @@
+use crate::array_sprite::array_text;
+use crate::combo::Scoring;
+use crate::dot_map::DotMap;
+use crate::game_mode::ModeKind;
+use crate::input::Actions;
+use crate::key_binding::Control;
+use crate::math::Vec2;
+use crate::storage::Storage;
+use std::cmp::Reverse;
+
+// 保存する順位の数
+pub const TABLE_SIZE: usize = 10;
+
+#[derive(Clone)]
+pub struct ScoreEntry {
+    pub name: String, // イニシャル(3文字)
+    pub score: i32,
+}
+
+// ルールと得点の計算方法に対応する表の得点の計算方法
+// モダンの得点はボーナスを含み、アーケード版と比べられないので別の表にする
+// タイムアタックはクリアタイムを載せるので分けない
+pub fn table_scoring(kind: ModeKind, scoring: Scoring) -> Scoring {
+    if kind.lower_is_better() {
+        Scoring::Classic
+    } else {
+        scoring
+    }
+}
+
+// 得点の高い順(タイムアタックはクリアタイムの短い順)に並んだハイスコア表
+pub struct HighScoreTable {
+    pub entries: Vec<ScoreEntry>,
+    // どのルールの表か
+    kind: ModeKind,
+    // どの得点の計算方法の表か
+    scoring: Scoring,
+}
+
+impl HighScoreTable {
+    // すべてのハイスコア表を読み込む(アーケード版と同じ得点の表、モダンの得点の表の順)
+    pub fn load_all(storage: &dyn Storage) -> Vec<Self> {
+        [Scoring::Classic, Scoring::Modern]
+            .iter()
+            .flat_map(|scoring| {
+                ModeKind::ALL
+                    .iter()
+                    .filter(move |kind| table_scoring(**kind, *scoring) == *scoring)
+                    .map(move |kind| HighScoreTable::load(storage, *kind, *scoring))
+            })
+            .collect()
+    }
+    // 保存先から読み込む(保存されていなければ空の表)
+    // 1行に「イニシャル,得点」の形式で保存されている
+    pub fn load(storage: &dyn Storage, kind: ModeKind, scoring: Scoring) -> Self {
+        let mut entries = Vec::new();
+        if let Some(text) = storage.load(&storage_key(kind, scoring)) {
+            for line in text.lines() {
+                let mut fields = line.split(',');
+                let name = fields.next();
+                let score = fields.next().and_then(|s| s.trim().parse().ok());
+                if let (Some(name), Some(score)) = (name, score) {
+                    entries.push(ScoreEntry {
+                        name: name.to_string(),
+                        score,
+                    });
+                } else {
+                    log::info!("ハイスコアの{}は不正なので読み飛ばします。", line);
+                }
+            }
+        }
+        let mut table = HighScoreTable {
+            entries,
+            kind,
+            scoring,
+        };
+        if kind.lower_is_better() {
+            table.entries.sort_by_key(|e| e.score);
+        } else {
+            table.entries.sort_by_key(|e| Reverse(e.score));
+        }
+        table.entries.truncate(TABLE_SIZE);
+        table
+    }
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let text: Vec<String> = self
+            .entries
+            .iter()
+            .map(|e| format!("{},{}", e.name, e.score))
+            .collect();
+        storage.save(&storage_key(self.kind, self.scoring), &text.join("\n"));
+    }
+    // ルールと得点の計算方法で記録する表ならば真
+    pub fn is_for(&self, kind: ModeKind, scoring: Scoring) -> bool {
+        self.kind == kind && self.scoring == table_scoring(kind, scoring)
+    }
+    // タイトル画面の後に表示するならば真(モダンの得点の表は記録がある場合のみ表示する)
+    pub fn is_shown(&self) -> bool {
+        self.scoring == Scoring::Classic || !self.entries.is_empty()
+    }
+    // scoreがotherより上位ならば真
+    fn is_better(&self, score: i32, other: i32) -> bool {
+        if self.kind.lower_is_better() {
+            score < other
+        } else {
+            other < score
+        }
+    }
+    // 表の最高得点(表が空ならば0)
+    pub fn high_score(&self) -> i32 {
+        self.entries.first().map_or(0, |e| e.score)
+    }
+    // 表に載る得点ならば真を返す
+    pub fn qualifies(&self, score: i32) -> bool {
+        if score <= 0 {
+            return false;
+        }
+        match self.entries.get(TABLE_SIZE - 1) {
+            Some(last) => self.is_better(score, last.score),
+            None => true,
+        }
+    }
+    // 得点を追加して順位(0始まり)を返す、表に載らなければNone
+    pub fn insert(&mut self, name: &str, score: i32) -> Option<usize> {
+        if !self.qualifies(score) {
+            return None;
+        }
+        // 同点の場合は先に登録された方を上位にする
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| self.is_better(score, e.score))
+            .unwrap_or(self.entries.len());
+        self.entries.insert(
+            rank,
+            ScoreEntry {
+                name: name.to_string(),
+                score,
+            },
+        );
+        self.entries.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+    // ハイスコア表を画面中央に描画
+    pub fn draw(&self, dot_map: &mut DotMap, char_sprite: &[Vec<u8>]) {
+        let title = match self.kind {
+            ModeKind::Classic => "HIGH SCORES".to_string(),
+            kind => kind.name().to_string(),
+        };
+        let title = match self.scoring {
+            Scoring::Classic => title,
+            Scoring::Modern => format!("MODERN {}", title),
+        };
+        let title_x = (dot_map.map[0].len() as i32 - 8 * title.len() as i32) / 2;
+        array_text(
+            &mut dot_map.map,
+            Vec2::new(title_x, 16),
+            &title,
+            char_sprite,
+        );
+        for i in 0..TABLE_SIZE {
+            let text = match self.entries.get(i) {
+                Some(e) => format!(
+                    "{:>2} {:<3} {}",
+                    i + 1,
+                    e.name,
+                    self.kind.format_value(e.score)
+                ),
+                None => format!("{:>2} --- -----", i + 1),
+            };
+            let pos = Vec2::new(64, 40 + 16 * i as i32);
+            array_text(&mut dot_map.map, pos, &text, char_sprite);
+        }
+    }
+}
+
+// ハイスコア表の保存先のキー(モダンの得点の表は末尾に-modernを付ける)
+fn storage_key(kind: ModeKind, scoring: Scoring) -> String {
+    match scoring {
+        Scoring::Classic => kind.storage_key().to_string(),
+        Scoring::Modern => format!("{}-modern", kind.storage_key()),
+    }
+}
+
+// アーケード風のイニシャル入力
+// 左右キーで文字を選び、ショットボタンで確定する
+pub struct InitialsEntry {
+    pub player: usize, // 入力中のプレイヤー(0: 1P, 1: 2P)
+    pub score: i32,
+    // どのルールと得点の計算方法の表に登録するか
+    pub kind: ModeKind,
+    pub scoring: Scoring,
+    letters: [u8; 3],
+    // 入力中の文字の位置
+    pub cursor: usize,
+    // 左右キーを押し続けた時のリピートまでの残りカウント
+    repeat_cnt: i32,
+    // 入力中の文字を点滅させるためのカウント
+    blink_cnt: i32,
+}
+
+impl InitialsEntry {
+    pub fn new(player: usize, score: i32, kind: ModeKind, scoring: Scoring) -> Self {
+        InitialsEntry {
+            player,
+            score,
+            kind,
+            scoring,
+            letters: [b'A'; 3],
+            cursor: 0,
+            repeat_cnt: 0,
+            blink_cnt: 0,
+        }
+    }
+    // 左右キーとショットボタンは押された瞬間に反応する(ゲーム中から押しっぱなしのキーには反応しない)
+    pub fn update(&mut self, actions: &Actions) {
+        if self.is_done() {
+            return;
+        }
+        self.blink_cnt += 1;
+        let letter = &mut self.letters[self.cursor];
+        let left = actions.held(Control::Left);
+        if left || actions.held(Control::Right) {
+            let pressed =
+                actions.just_pressed(Control::Left) || actions.just_pressed(Control::Right);
+            if pressed {
+                self.repeat_cnt = 20;
+            } else {
+                self.repeat_cnt -= 1;
+            }
+            if pressed || self.repeat_cnt < 0 {
+                if self.repeat_cnt < 0 {
+                    self.repeat_cnt = 6;
+                }
+                *letter = if left {
+                    if *letter == b'A' {
+                        b'Z'
+                    } else {
+                        *letter - 1
+                    }
+                } else if *letter == b'Z' {
+                    b'A'
+                } else {
+                    *letter + 1
+                };
+            }
+        }
+        // ショットボタンを押した瞬間に文字を確定する
+        if actions.just_pressed(Control::Shot) {
+            self.cursor += 1;
+        }
+    }
+    // 3文字すべて確定したら真
+    pub fn is_done(&self) -> bool {
+        self.letters.len() <= self.cursor
+    }
+    pub fn name(&self) -> String {
+        self.letters.iter().map(|c| *c as char).collect()
+    }
+    // 入力画面を描画(入力中の文字は点滅させる)
+    pub fn draw(&self, dot_map: &mut DotMap, char_sprite: &[Vec<u8>]) {
+        let blink = self.blink_cnt / 8 % 2 == 0;
+        array_text(
+            &mut dot_map.map,
+            Vec2::new(32, 40),
+            "ENTER YOUR INITIALS",
+            char_sprite,
+        );
+        let text = format!(
+            "PLAYER<{}> {}",
+            self.player + 1,
+            self.kind.format_value(self.score)
+        );
+        array_text(&mut dot_map.map, Vec2::new(48, 72), &text, char_sprite);
+        let mut pos = Vec2::new(100, 104);
+        for (i, c) in self.letters.iter().enumerate() {
+            let c = if i == self.cursor && blink {
+                ' '
+            } else if self.cursor < i {
+                '-'
+            } else {
+                *c as char
+            };
+            array_text(&mut dot_map.map, pos, &c.to_string(), char_sprite);
+            pos.x += 8;
+        }
+        array_text(
+            &mut dot_map.map,
+            Vec2::new(16, 152),
+            "LEFT/RIGHT: SELECT",
+            char_sprite,
+        );
+        array_text(
+            &mut dot_map.map,
+            Vec2::new(16, 168),
+            "SHOT: OK",
+            char_sprite,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut storage = MemoryStorage::default();
+        let mut table = HighScoreTable::load(&storage, ModeKind::Classic, Scoring::Classic);
+        assert!(table.entries.is_empty());
+        table.insert("AAA", 300);
+        table.insert("BBB", 500);
+        table.save(&mut storage);
+
+        let loaded = HighScoreTable::load(&storage, ModeKind::Classic, Scoring::Classic);
+        let entries: Vec<(&str, i32)> = loaded
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.score))
+            .collect();
+        assert_eq!(entries, [("BBB", 500), ("AAA", 300)]);
+        // 他のルールの表には保存されない
+        assert!(
+            HighScoreTable::load(&storage, ModeKind::ScoreAttack, Scoring::Classic)
+                .entries
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn modern_scores_have_their_own_tables() {
+        let mut storage = MemoryStorage::default();
+        let tables = HighScoreTable::load_all(&storage);
+        // タイムアタックだけはモダンの表がない
+        assert_eq!(tables.len(), ModeKind::ALL.len() * 2 - 1);
+        let modern = tables
+            .iter()
+            .position(|t| t.is_for(ModeKind::Classic, Scoring::Modern))
+            .unwrap();
+        assert!(!tables[modern].is_for(ModeKind::Classic, Scoring::Classic));
+        assert!(!tables[modern].is_shown());
+
+        let mut table = HighScoreTable::load(&storage, ModeKind::Classic, Scoring::Modern);
+        table.insert("MOD", 9000);
+        table.save(&mut storage);
+        let tables = HighScoreTable::load_all(&storage);
+        assert_eq!(tables[modern].high_score(), 9000);
+        assert!(tables[modern].is_shown());
+        // アーケード版と同じ得点の表には載らない
+        let classic = HighScoreTable::load(&storage, ModeKind::Classic, Scoring::Classic);
+        assert!(classic.entries.is_empty());
+        // タイムアタックのクリアタイムは得点の計算方法によらず同じ表に載せる
+        let time_attack = tables
+            .iter()
+            .filter(|t| t.is_for(ModeKind::TimeAttack, Scoring::Modern))
+            .count();
+        assert_eq!(time_attack, 1);
+        assert!(tables
+            .iter()
+            .any(|t| t.is_for(ModeKind::TimeAttack, Scoring::Classic) && t.is_shown()));
+    }
+
+    #[test]
+    fn insert_ranks_and_truncates() {
+        let mut table = HighScoreTable::load(
+            &MemoryStorage::default(),
+            ModeKind::Classic,
+            Scoring::Classic,
+        );
+        for i in 1..=TABLE_SIZE as i32 {
+            assert_eq!(table.insert("AAA", i * 100), Some(0));
+        }
+        assert_eq!(table.entries.len(), TABLE_SIZE);
+        // 最下位以下の得点と0点は載らない
+        assert!(!table.qualifies(100));
+        assert_eq!(table.insert("BBB", 100), None);
+        assert_eq!(table.insert("BBB", 0), None);
+        // 同点の場合は先に登録された方が上位
+        assert_eq!(table.insert("CCC", 500), Some(6));
+        assert_eq!(table.entries.len(), TABLE_SIZE);
+        assert_eq!(table.entries.last().unwrap().score, 200);
+        assert_eq!(table.high_score(), 1000);
+    }
+
+    #[test]
+    fn time_attack_ranks_lower_first() {
+        let mut table = HighScoreTable::load(
+            &MemoryStorage::default(),
+            ModeKind::TimeAttack,
+            Scoring::Classic,
+        );
+        table.insert("AAA", 3000);
+        assert_eq!(table.insert("BBB", 2000), Some(0));
+        assert_eq!(table.insert("CCC", 4000), Some(2));
+        assert_eq!(table.high_score(), 2000);
+    }
+
+    #[test]
+    fn load_skips_malformed_lines() {
+        let mut storage = MemoryStorage::default();
+        let text = "AAA,100\nBROKEN\nBBB,abc\n\nCCC, 300\n";
+        storage.save(ModeKind::Classic.storage_key(), text);
+        let table = HighScoreTable::load(&storage, ModeKind::Classic, Scoring::Classic);
+        let entries: Vec<(&str, i32)> = table
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.score))
+            .collect();
+        assert_eq!(entries, [("CCC", 300), ("AAA", 100)]);
+    }
+
+    #[test]
+    fn load_sorts_and_truncates_stored_entries() {
+        let mut storage = MemoryStorage::default();
+        let text: Vec<String> = (1..=15).map(|i| format!("AAA,{}", i * 10)).collect();
+        storage.save(ModeKind::Classic.storage_key(), &text.join("\n"));
+        let table = HighScoreTable::load(&storage, ModeKind::Classic, Scoring::Classic);
+        assert_eq!(table.entries.len(), TABLE_SIZE);
+        assert_eq!(table.high_score(), 150);
+        assert_eq!(table.entries.last().unwrap().score, 60);
+    }
+
+    #[test]
+    fn initials_entry_selects_and_confirms_letters() {
+        let mut entry = InitialsEntry::new(0, 1000, ModeKind::Classic, Scoring::Classic);
+        let mut actions = Actions::default();
+        // 1文字目: 左でZに戻る
+        actions.sample_controls(&[Control::Left]);
+        entry.update(&actions);
+        actions.sample_controls(&[]);
+        entry.update(&actions);
+        actions.sample_controls(&[Control::Shot]);
+        entry.update(&actions);
+        // 押しっぱなしのショットボタンでは次の文字を確定しない
+        actions.sample_controls(&[Control::Shot]);
+        entry.update(&actions);
+        assert_eq!(entry.cursor, 1);
+        // 2文字目: 右を2回押してC
+        for _ in 0..2 {
+            actions.sample_controls(&[Control::Right]);
+            entry.update(&actions);
+            actions.sample_controls(&[]);
+            entry.update(&actions);
+        }
+        actions.sample_controls(&[Control::Shot]);
+        entry.update(&actions);
+        actions.sample_controls(&[]);
+        entry.update(&actions);
+        // 3文字目: Aのまま確定
+        actions.sample_controls(&[Control::Shot]);
+        entry.update(&actions);
+        assert!(entry.is_done());
+        assert_eq!(entry.name(), "ZCA");
+        // 確定後は入力を受け付けない
+        actions.sample_controls(&[Control::Right]);
+        entry.update(&actions);
+        assert_eq!(entry.name(), "ZCA");
+    }
+
+    #[test]
+    fn initials_entry_repeats_held_key() {
+        let mut entry = InitialsEntry::new(0, 1000, ModeKind::Classic, Scoring::Classic);
+        let mut actions = Actions::default();
+        actions.sample_controls(&[Control::Right]);
+        entry.update(&actions);
+        assert_eq!(entry.name(), "BAA");
+        // 押し続けると20tick後から6tickごとに進む
+        for _ in 0..20 {
+            actions.sample_controls(&[Control::Right]);
+            entry.update(&actions);
+        }
+        assert_eq!(entry.name(), "BAA");
+        actions.sample_controls(&[Control::Right]);
+        entry.update(&actions);
+        assert_eq!(entry.name(), "CAA");
+    }
+}
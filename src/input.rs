@@ -1,81 +1,260 @@
+use crate::gamepad::GamepadInput;
+use crate::key_binding::{Control, KeyBindings};
+use crate::listener::Listener;
+use crate::touch::TouchInput;
 use std::cell::RefCell;
 use std::rc::Rc;
-use wasm_bindgen::{prelude::Closure, JsCast};
-use web_sys::HtmlCanvasElement;
+use web_sys::{Event, KeyboardEvent};
 
 // 対応するキーが押されている時真
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 pub struct KeyDown {
-    pub left: bool,  // プレイヤーを左へ移動させる
-    pub right: bool, // プレイヤーを右へ移動させる
-    pub shot: bool,  // プレイヤーが弾を撃つ
-    pub pause: bool, // ポーズする
+    pub left: bool,     // プレイヤーを左へ移動させる
+    pub right: bool,    // プレイヤーを右へ移動させる
+    pub up: bool,       // メニューのカーソルを上へ移動させる
+    pub down: bool,     // メニューのカーソルを下へ移動させる
+    pub shot: bool,     // プレイヤーが弾を撃つ
+    pub pause: bool,    // ポーズする
+    pub start1: bool,   // 1人プレイで開始する
+    pub start2: bool,   // 2人プレイで開始する
+    pub options: bool,  // 設定画面を開く
+    pub coin: bool,     // コインを投入する
+    pub practice: bool, // 練習モードの設定画面を開く
+    pub mode: bool,     // タイトル画面でルールを切り替える
+    pub history: bool,  // このセッションで遊んだゲームの記録を表示する
+    pub gallery: bool,  // 実績のギャラリー画面を表示する
 }
 
-pub fn input_setup(input_key: &Rc<RefCell<KeyDown>>) {
-    // キー入力処理
-    let key_down = Rc::clone(&input_key);
-    let document = web_sys::window().unwrap().document().unwrap();
-    let body = document.body().unwrap();
-    let canvas = document
-        .create_element("canvas")
-        .unwrap()
-        .dyn_into::<HtmlCanvasElement>()
-        .unwrap();
-    body.append_child(&canvas).unwrap();
-    // キー押し下げ
-
-    let closure_key_down = Closure::wrap(Box::new(move |e: web_sys::KeyboardEvent| {
-        function_key_down(e, &mut key_down.borrow_mut());
-    }) as Box<dyn FnMut(_)>);
-
-    body.add_event_listener_with_callback("keydown", closure_key_down.as_ref().unchecked_ref())
-        .unwrap();
-    closure_key_down.forget();
+impl KeyDown {
+    // 操作に対応する項目
+    pub fn get(&self, control: Control) -> bool {
+        match control {
+            Control::Left => self.left,
+            Control::Right => self.right,
+            Control::Up => self.up,
+            Control::Down => self.down,
+            Control::Shot => self.shot,
+            Control::Pause => self.pause,
+            Control::Start1 => self.start1,
+            Control::Start2 => self.start2,
+            Control::Options => self.options,
+            Control::Coin => self.coin,
+            Control::Practice => self.practice,
+            Control::Mode => self.mode,
+            Control::History => self.history,
+            Control::Gallery => self.gallery,
+        }
+    }
+    // 操作に対応する項目を変更する
+    pub fn set(&mut self, control: Control, pressed: bool) {
+        let flag = match control {
+            Control::Left => &mut self.left,
+            Control::Right => &mut self.right,
+            Control::Up => &mut self.up,
+            Control::Down => &mut self.down,
+            Control::Shot => &mut self.shot,
+            Control::Pause => &mut self.pause,
+            Control::Start1 => &mut self.start1,
+            Control::Start2 => &mut self.start2,
+            Control::Options => &mut self.options,
+            Control::Coin => &mut self.coin,
+            Control::Practice => &mut self.practice,
+            Control::Mode => &mut self.mode,
+            Control::History => &mut self.history,
+            Control::Gallery => &mut self.gallery,
+        };
+        *flag = pressed;
+    }
+}
 
-    // キー押し上げ
-    let key_up = Rc::clone(&input_key);
-    let closure_key_up = Closure::wrap(Box::new(move |e: web_sys::KeyboardEvent| {
-        function_key_up(e, &mut key_up.borrow_mut());
-    }) as Box<dyn FnMut(_)>);
-    body.add_event_listener_with_callback("keyup", closure_key_up.as_ref().unchecked_ref())
-        .unwrap();
-    closure_key_up.forget();
+// 1tickに1回読み取った各操作の状態
+// 押された瞬間、押している間、離された瞬間を返す(同じtickの中では何度読んでも同じ結果になる)
+#[derive(Default, Clone)]
+pub struct Actions {
+    held: [bool; Control::ALL.len()],
+    // 前のtickで押していたか
+    pre_held: [bool; Control::ALL.len()],
 }
-// キー押し下げ時に呼び出し
-fn function_key_down(event: web_sys::KeyboardEvent, input_data: &mut KeyDown) {
-    match &*event.key() {
-        "ArrowLeft" | "a" => {
-            input_data.left = true;
+
+impl Actions {
+    // tickの最初に1回だけ呼び出し、その時点の入力を読み取る
+    pub fn sample(&mut self, input_key: &KeyDown) {
+        self.pre_held = self.held;
+        for (held, control) in self.held.iter_mut().zip(Control::ALL) {
+            *held = input_key.get(control);
         }
-        "ArrowRight" | "d" => {
-            input_data.right = true;
+    }
+    // テスト用に、指定した操作だけを押している入力を読み取る
+    #[cfg(test)]
+    pub(crate) fn sample_controls(&mut self, controls: &[Control]) {
+        let mut input_key = KeyDown::default();
+        for control in controls {
+            input_key.set(*control, true);
         }
-        "Space" | "Enter" | "z" => {
-            input_data.shot = true;
+        self.sample(&input_key);
+    }
+    pub fn held(&self, control: Control) -> bool {
+        self.held[control.index()]
+    }
+    pub fn just_pressed(&self, control: Control) -> bool {
+        self.held[control.index()] && !self.pre_held[control.index()]
+    }
+    pub fn just_released(&self, control: Control) -> bool {
+        !self.held[control.index()] && self.pre_held[control.index()]
+    }
+}
+
+// キーボードの状態(キーイベントで更新する)
+#[derive(Default)]
+pub struct Keyboard {
+    pub key_down: KeyDown,
+    // 操作ごとに割り当てたキー
+    pub bindings: KeyBindings,
+    // 最後に押されたキー(キー割り当ての変更画面で読み取る、キーリピートは含まない)
+    pub last_key: Option<String>,
+}
+
+// キーボード、ゲームパッド、タッチ操作の入力をまとめる
+// キーボードのイベントリスナーを保持し、破棄する時に登録を解除する
+pub struct InputManager {
+    pub keyboard: Rc<RefCell<Keyboard>>,
+    pub gamepad: GamepadInput,
+    pub touch: TouchInput,
+    listeners: Vec<Listener>,
+}
+
+impl InputManager {
+    pub fn new(bindings: KeyBindings) -> Self {
+        InputManager {
+            keyboard: Rc::new(RefCell::new(Keyboard {
+                bindings,
+                ..Keyboard::default()
+            })),
+            gamepad: GamepadInput::new(),
+            touch: TouchInput::new(),
+            listeners: Vec::new(),
         }
-        "Escape" => {
-            input_data.pause = true;
+    }
+    // イベントリスナーを登録する(登録済みならば何もしない)
+    // ウィンドウのフォーカスやタブの表示状態が変わるとon_focus_changeを呼び出す(戻った時に真)
+    pub fn setup<F>(&mut self, on_focus_change: F)
+    where
+        F: Fn(bool) + Clone + 'static,
+    {
+        if !self.listeners.is_empty() {
+            return;
         }
-        _ => (),
-    };
+        let window = web_sys::window().unwrap();
+        let document = window.document().unwrap();
+        let body = document.body().unwrap();
+        // キー押し下げ
+        let keyboard = Rc::clone(&self.keyboard);
+        self.listeners
+            .push(Listener::new(&body, "keydown", move |e: KeyboardEvent| {
+                function_key_down(e, &mut keyboard.borrow_mut());
+            }));
+        // キー押し上げ
+        let keyboard = Rc::clone(&self.keyboard);
+        self.listeners
+            .push(Listener::new(&body, "keyup", move |e: KeyboardEvent| {
+                function_key_up(e, &mut keyboard.borrow_mut());
+            }));
+        // ウィンドウのフォーカスを失った、戻った
+        let on_change = on_focus_change.clone();
+        self.listeners
+            .push(Listener::new(&window, "blur", move |_: Event| {
+                on_change(false)
+            }));
+        let on_change = on_focus_change.clone();
+        self.listeners
+            .push(Listener::new(&window, "focus", move |_: Event| {
+                on_change(true)
+            }));
+        // タブが隠れた、表示された(隠れている間はrequestAnimationFrameが止まる)
+        let hidden_document = document.clone();
+        self.listeners.push(Listener::new(
+            &document,
+            "visibilitychange",
+            move |_: Event| on_focus_change(!hidden_document.hidden()),
+        ));
+        self.gamepad.setup();
+    }
+    // イベントリスナーの登録を解除する(破棄する時にも解除される)
+    pub fn teardown(&mut self) {
+        self.listeners.clear();
+        self.gamepad.teardown();
+    }
+    // 押しているキーとタッチ操作のボタンをすべて離したことにする
+    // フォーカスを失うとkeyupが届かず、押したままになるのを防ぐ
+    pub fn release_all(&mut self) {
+        let mut keyboard = self.keyboard.borrow_mut();
+        keyboard.key_down = KeyDown::default();
+        keyboard.last_key = None;
+        self.touch.release_all();
+    }
+    // キーボードの入力にゲームパッドとタッチ操作の入力を加える
+    // dead_zoneはゲームパッドのスティックの遊び(0～1)
+    pub fn read(&self, dead_zone: f64) -> KeyDown {
+        let mut input_key = self.keyboard.borrow().key_down.clone();
+        self.gamepad.poll(&mut input_key, dead_zone);
+        self.touch.apply(&mut input_key);
+        input_key
+    }
+}
+
+// キー押し下げ時に呼び出し
+fn function_key_down(event: KeyboardEvent, keyboard: &mut Keyboard) {
+    let key = event.key();
+    if !event.repeat() {
+        keyboard.last_key = Some(key.clone());
+    }
+    if let Some(control) = keyboard.bindings.control_of(&key) {
+        keyboard.key_down.set(control, true);
+    }
 }
 
 // キー押し上げ時に呼び出し
-fn function_key_up(event: web_sys::KeyboardEvent, input_data: &mut KeyDown) {
-    match &*event.key() {
-        "ArrowLeft" | "a" => {
-            input_data.left = false;
-        }
-        "ArrowRight" | "d" => {
-            input_data.right = false;
-        }
-        "Space" | "Enter" | "z" => {
-            input_data.shot = false;
-        }
-        "Escape" => {
-            input_data.pause = false;
-        }
-        _ => (),
-    };
+fn function_key_up(event: KeyboardEvent, keyboard: &mut Keyboard) {
+    if let Some(control) = keyboard.bindings.control_of(&event.key()) {
+        keyboard.key_down.set(control, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_hold_release_sequence() {
+        let mut actions = Actions::default();
+        actions.sample_controls(&[Control::Shot]);
+        assert!(actions.just_pressed(Control::Shot));
+        assert!(actions.held(Control::Shot));
+        assert!(!actions.just_released(Control::Shot));
+        // 押し続けている間は押された瞬間にならない
+        actions.sample_controls(&[Control::Shot]);
+        assert!(!actions.just_pressed(Control::Shot));
+        assert!(actions.held(Control::Shot));
+        actions.sample_controls(&[]);
+        assert!(!actions.held(Control::Shot));
+        assert!(actions.just_released(Control::Shot));
+        // 離された瞬間は1tickだけ
+        actions.sample_controls(&[]);
+        assert!(!actions.just_released(Control::Shot));
+        // 押された瞬間は同じtickの中では何度読んでも同じ
+        actions.sample_controls(&[Control::Shot]);
+        assert!(actions.just_pressed(Control::Shot));
+        assert!(actions.just_pressed(Control::Shot));
+    }
+
+    #[test]
+    fn controls_are_independent() {
+        let mut actions = Actions::default();
+        actions.sample_controls(&[Control::Left]);
+        actions.sample_controls(&[Control::Left, Control::Shot]);
+        assert!(actions.held(Control::Left));
+        assert!(!actions.just_pressed(Control::Left));
+        assert!(actions.just_pressed(Control::Shot));
+        assert!(!actions.held(Control::Right));
+    }
 }
@@ -0,0 +1,384 @@
+use crate::storage::Storage;
+
+const STORAGE_KEY: &str = "invader-key-bindings";
+// 1つの操作に割り当てられるキーの数の上限
+const MAX_KEYS: usize = 3;
+
+// キーを割り当てる操作(KeyDownの各項目に対応する)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Control {
+    Left,
+    Right,
+    Up,
+    Down,
+    Shot,
+    Pause,
+    Start1,
+    Start2,
+    Options,
+    Coin,
+    Practice,
+    Mode,
+    History,
+    Gallery,
+}
+
+impl Control {
+    pub const ALL: [Control; 14] = [
+        Control::Left,
+        Control::Right,
+        Control::Up,
+        Control::Down,
+        Control::Shot,
+        Control::Pause,
+        Control::Start1,
+        Control::Start2,
+        Control::Options,
+        Control::Coin,
+        Control::Practice,
+        Control::Mode,
+        Control::History,
+        Control::Gallery,
+    ];
+    // キー割り当ての変更画面で変更できる操作
+    pub const REBINDABLE: [Control; 8] = [
+        Control::Left,
+        Control::Right,
+        Control::Up,
+        Control::Down,
+        Control::Shot,
+        Control::Pause,
+        Control::Coin,
+        Control::Start1,
+    ];
+
+    // 保存に使う名前
+    fn key(self) -> &'static str {
+        match self {
+            Control::Left => "left",
+            Control::Right => "right",
+            Control::Up => "up",
+            Control::Down => "down",
+            Control::Shot => "shot",
+            Control::Pause => "pause",
+            Control::Start1 => "start1",
+            Control::Start2 => "start2",
+            Control::Options => "options",
+            Control::Coin => "coin",
+            Control::Practice => "practice",
+            Control::Mode => "mode",
+            Control::History => "history",
+            Control::Gallery => "gallery",
+        }
+    }
+    // 画面に表示する名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Control::Left => "LEFT",
+            Control::Right => "RIGHT",
+            Control::Up => "UP",
+            Control::Down => "DOWN",
+            Control::Shot => "SHOT",
+            Control::Pause => "PAUSE",
+            Control::Start1 => "1P START",
+            Control::Start2 => "2P START",
+            Control::Options => "OPTIONS",
+            Control::Coin => "COIN",
+            Control::Practice => "PRACTICE",
+            Control::Mode => "MODE",
+            Control::History => "HISTORY",
+            Control::Gallery => "GALLERY",
+        }
+    }
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|c| *c == self).unwrap()
+    }
+    // 初期設定のキー(KeyboardEvent.keyの値)
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Control::Left => &["ArrowLeft", "a"],
+            Control::Right => &["ArrowRight", "d"],
+            Control::Up => &["ArrowUp", "w"],
+            Control::Down => &["ArrowDown", "s"],
+            Control::Shot => &[" ", "Enter", "z"],
+            Control::Pause => &["Escape"],
+            Control::Start1 => &["1"],
+            Control::Start2 => &["2"],
+            Control::Options => &["o"],
+            Control::Coin => &["c", "5"],
+            Control::Practice => &["p"],
+            Control::Mode => &["m"],
+            Control::History => &["h"],
+            Control::Gallery => &["g"],
+        }
+    }
+}
+
+// 他の操作に割り当て済みのキーを移した時の、元の操作と代わりに渡したキー
+#[derive(Debug, PartialEq)]
+pub struct MovedKey {
+    pub from: Control,
+    // 元の操作にキーが残らない場合は、割り当て先の操作で最も優先していたキーと交換する
+    pub swapped: Option<String>,
+}
+
+// 操作ごとに割り当てたキーの表(1つの操作に複数のキーを割り当てられる)
+// どの操作にも必ず1つ以上のキーを割り当てる(メニューを操作できなくならないようにする)
+#[derive(Clone)]
+pub struct KeyBindings {
+    // Control::ALLの順に、割り当てたキー(KeyboardEvent.keyの値)
+    keys: Vec<Vec<String>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            keys: Control::ALL
+                .iter()
+                .map(|c| c.default_keys().iter().map(|k| k.to_string()).collect())
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    // 保存先から読み込む(保存されていない操作は初期設定のキーにする)
+    // 1行に「操作名=キー,キー」の形式で保存されている
+    pub fn load(storage: &dyn Storage) -> Self {
+        let mut bindings = KeyBindings::default();
+        if let Some(text) = storage.load(STORAGE_KEY) {
+            for line in text.lines() {
+                let control = line.split_once('=').and_then(|(name, keys)| {
+                    Some((Control::ALL.iter().find(|c| c.key() == name)?, keys))
+                });
+                match control {
+                    Some((control, keys)) => {
+                        let keys: Vec<String> = keys
+                            .split(',')
+                            .filter(|k| !k.is_empty())
+                            .map(decode_key)
+                            .take(MAX_KEYS)
+                            .collect();
+                        // キーが1つもなければ初期設定のままにする
+                        if !keys.is_empty() {
+                            bindings.keys[control.index()] = keys;
+                        }
+                    }
+                    None => log::info!("キー割り当ての{}は不正なので読み飛ばします。", line),
+                }
+            }
+        }
+        bindings
+    }
+    pub fn save(&self, storage: &mut dyn Storage) {
+        let text: Vec<String> = Control::ALL
+            .iter()
+            .map(|c| {
+                let keys: Vec<&str> = self.keys(*c).iter().map(|k| encode_key(k)).collect();
+                format!("{}={}", c.key(), keys.join(","))
+            })
+            .collect();
+        storage.save(STORAGE_KEY, &text.join("\n"));
+    }
+    pub fn keys(&self, control: Control) -> &[String] {
+        &self.keys[control.index()]
+    }
+    // キーが割り当てられている操作
+    pub fn control_of(&self, key: &str) -> Option<Control> {
+        let key = normalize_key(key);
+        Control::ALL
+            .iter()
+            .copied()
+            .find(|c| self.keys(*c).contains(&key))
+    }
+    // 操作にキーを割り当てる(最も優先するキーとして先頭に加え、上限を超えたら古いものから外す)
+    // 他の操作に割り当て済みのキーはその操作から外して移し、移した情報を返す
+    pub fn bind(&mut self, control: Control, key: &str) -> Option<MovedKey> {
+        let key = normalize_key(key);
+        let moved = self
+            .control_of(&key)
+            .filter(|other| *other != control)
+            .map(|other| {
+                self.keys[other.index()].retain(|k| *k != key);
+                // 最後のキーを移す場合は、代わりに割り当て先の先頭のキーを渡す
+                let swapped = if self.keys[other.index()].is_empty() {
+                    let swapped = self.keys[control.index()].remove(0);
+                    self.keys[other.index()].push(swapped.clone());
+                    Some(swapped)
+                } else {
+                    None
+                };
+                MovedKey {
+                    from: other,
+                    swapped,
+                }
+            });
+        let keys = &mut self.keys[control.index()];
+        keys.retain(|k| *k != key);
+        keys.insert(0, key);
+        keys.truncate(MAX_KEYS);
+        moved
+    }
+}
+
+// 大文字小文字を区別しないように1文字のキーは小文字にそろえる
+pub fn normalize_key(key: &str) -> String {
+    if key.chars().count() == 1 {
+        key.to_lowercase()
+    } else {
+        key.to_string()
+    }
+}
+
+// 画面に表示するキーの名前(ドット文字で描ける文字のみにする)
+pub fn key_label(key: &str) -> String {
+    match key {
+        " " => "SPACE".to_string(),
+        "ArrowLeft" => "LEFT".to_string(),
+        "ArrowRight" => "RIGHT".to_string(),
+        "ArrowUp" => "UP".to_string(),
+        "ArrowDown" => "DOWN".to_string(),
+        "Escape" => "ESC".to_string(),
+        "Control" => "CTRL".to_string(),
+        _ => {
+            let label: String = key
+                .to_uppercase()
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric() || "<>-=*?!.:/%+".contains(*c))
+                .take(6)
+                .collect();
+            // 描ける文字がなければ?にする
+            if label.is_empty() {
+                "?".to_string()
+            } else {
+                label
+            }
+        }
+    }
+}
+
+// 保存する文字列の区切りと紛らわしいキーは名前で保存する
+fn encode_key(key: &str) -> &str {
+    match key {
+        " " => "Space",
+        "," => "Comma",
+        _ => key,
+    }
+}
+fn decode_key(key: &str) -> String {
+    match key {
+        "Space" => " ".to_string(),
+        "Comma" => ",".to_string(),
+        _ => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Control::Shot, "=");
+        bindings.bind(Control::Coin, ",");
+        bindings.bind(Control::Pause, " ");
+        let mut storage = MemoryStorage::default();
+        bindings.save(&mut storage);
+        // 区切りと紛らわしいキーは名前で保存される
+        let text = storage.load(STORAGE_KEY).unwrap();
+        assert!(text.lines().any(|line| line == "coin=Comma,c,5"));
+        assert!(text.lines().any(|line| line == "pause=Space,Escape"));
+        // 発射は=を追加してzがあふれ、スペースをポーズに移した
+        assert!(text.lines().any(|line| line == "shot==,Enter"));
+
+        let loaded = KeyBindings::load(&storage);
+        for control in Control::ALL {
+            assert_eq!(loaded.keys(control), bindings.keys(control));
+        }
+        assert_eq!(loaded.control_of(" "), Some(Control::Pause));
+        assert_eq!(loaded.control_of(","), Some(Control::Coin));
+        assert_eq!(loaded.control_of("="), Some(Control::Shot));
+    }
+
+    #[test]
+    fn load_skips_unknown_lines_and_keeps_defaults() {
+        let mut storage = MemoryStorage::default();
+        storage.save(
+            STORAGE_KEY,
+            "left=j\nbogus=x\nno separator\nright=k,l,;,'\nshot=",
+        );
+        let loaded = KeyBindings::load(&storage);
+        assert_eq!(loaded.keys(Control::Left), ["j"]);
+        // 上限を超えた分は読み込まない
+        assert_eq!(loaded.keys(Control::Right), ["k", "l", ";"]);
+        // キーが1つもない操作は初期設定のまま
+        assert_eq!(loaded.keys(Control::Shot), [" ", "Enter", "z"]);
+    }
+
+    #[test]
+    fn normalize_key_lowercases_single_characters() {
+        assert_eq!(normalize_key("A"), "a");
+        assert_eq!(normalize_key("z"), "z");
+        assert_eq!(normalize_key(" "), " ");
+        assert_eq!(normalize_key("ArrowLeft"), "ArrowLeft");
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.control_of("A"), Some(Control::Left));
+    }
+
+    #[test]
+    fn bind_moves_key_from_other_control() {
+        // AZERTYのZQSD: zは発射から移動の上へ移す
+        let mut bindings = KeyBindings::default();
+        let moved = MovedKey {
+            from: Control::Shot,
+            swapped: None,
+        };
+        assert_eq!(bindings.bind(Control::Up, "Z"), Some(moved));
+        assert_eq!(bindings.keys(Control::Up), ["z", "ArrowUp", "w"]);
+        assert_eq!(bindings.keys(Control::Shot), [" ", "Enter"]);
+        assert_eq!(bindings.bind(Control::Left, "q"), None);
+        assert_eq!(bindings.control_of("z"), Some(Control::Up));
+        // 同じ操作に割り当て済みのキーは先頭に移すだけ
+        assert_eq!(bindings.bind(Control::Up, "w"), None);
+        assert_eq!(bindings.keys(Control::Up), ["w", "z", "ArrowUp"]);
+    }
+
+    #[test]
+    fn bind_swaps_last_key_of_other_control() {
+        let mut bindings = KeyBindings::default();
+        // 発射のキーを1つずつ下へ移すと、最後のキーは下の先頭のキーと交換する
+        bindings.bind(Control::Down, " ");
+        bindings.bind(Control::Down, "Enter");
+        let moved = MovedKey {
+            from: Control::Shot,
+            swapped: Some("Enter".to_string()),
+        };
+        assert_eq!(bindings.bind(Control::Down, "z"), Some(moved));
+        assert_eq!(bindings.keys(Control::Shot), ["Enter"]);
+        assert_eq!(bindings.keys(Control::Down), ["z", " ", "ArrowDown"]);
+        // 割り当て画面で変更できない操作も空にしない
+        let moved = MovedKey {
+            from: Control::Options,
+            swapped: Some("Enter".to_string()),
+        };
+        assert_eq!(bindings.bind(Control::Shot, "o"), Some(moved));
+        assert_eq!(bindings.keys(Control::Options), ["Enter"]);
+        assert_eq!(bindings.keys(Control::Shot), ["o"]);
+        for control in Control::ALL {
+            assert!(!bindings.keys(control).is_empty());
+        }
+    }
+
+    #[test]
+    fn bind_truncates_to_max_keys() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Control::Left, "j");
+        bindings.bind(Control::Left, "k");
+        assert_eq!(bindings.keys(Control::Left), ["k", "j", "ArrowLeft"]);
+        assert_eq!(bindings.keys(Control::Left).len(), MAX_KEYS);
+        // 外れたキーはどの操作にも割り当てられていない
+        assert_eq!(bindings.control_of("a"), None);
+    }
+}
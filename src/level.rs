@@ -0,0 +1,453 @@
+use crate::canvas;
+use crate::progression::StageParams;
+use crate::sprite::{self, DotShape};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Response};
+
+// キャンペーンで遊ぶレベルファイルの一覧
+const CAMPAIGN_FILE: &str = "levels/campaign.txt";
+// 隊列の列数と段数
+pub const COLUMN_NUM: usize = 11;
+pub const ROW_NUM: usize = 5;
+// 隊列の左端の初期位置のx座標と、エイリアン1匹分のマスの大きさ(ドット単位)
+pub const START_POS_X: i32 = 24;
+pub const CELL_SIZE: i32 = 16;
+// シールドの大きさ(ドット単位)
+pub const SHIELD_WIDTH: usize = 22;
+pub const SHIELD_HEIGHT: usize = 16;
+
+// レベルファイルの内容
+// 1行に「項目名=値」、隊列とシールドの形は「formation:」「shield:」の次の行から記述する
+// 省略した項目はアーケード版と同じ値になる
+#[derive(Clone)]
+pub struct Level {
+    pub name: String,
+    // リファレンスエイリアンの初期位置(画面下端からの距離)、Noneの場合はステージ数で決める
+    pub start_height: Option<i32>,
+    // エイリアンの横方向の移動量、Noneの場合はステージ数で決める
+    pub march_speed: Option<i32>,
+    // エイリアンの弾の移動速度、Noneの場合はステージ数で決める
+    pub bullet_speed: Option<i32>,
+    // 各マスのエイリアンの種類(0: タコ, 1: カニ, 2: イカ)、Noneは空きマス
+    // インデックス番号は一番下の段の左端を0とし、右、上の順に増える
+    pub formation: Vec<Option<usize>>,
+    // シールドの左端のx座標
+    pub shield_x: Vec<usize>,
+    // シールドの形(22x16ドット)
+    pub shield_map: Vec<Vec<bool>>,
+    // plunger shot、squiggly shotを撃つ列の表(1から11)
+    pub plunger_columns: Vec<usize>,
+    pub squiggly_columns: Vec<usize>,
+}
+
+impl Level {
+    // アーケード版と同じレベル
+    pub fn classic() -> Self {
+        Level {
+            name: "CLASSIC".to_string(),
+            start_height: None,
+            march_speed: None,
+            bullet_speed: None,
+            formation: (0..COLUMN_NUM * ROW_NUM)
+                .map(|i| Some(classic_alien_type(i / COLUMN_NUM)))
+                .collect(),
+            shield_x: vec![33, 78, 123, 168],
+            shield_map: sprite::ret_dot_data("shield").dot_map,
+            plunger_columns: vec![1, 7, 1, 1, 1, 4, 11, 1, 6, 3, 1, 1, 11, 9, 2, 8],
+            squiggly_columns: vec![11, 1, 6, 3, 1, 1, 11, 9, 2, 8, 2, 11, 4, 7, 10],
+        }
+    }
+    // レベルファイルの文字列を読み込む(不正な場合は行番号付きのエラーを返す)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut level = Level::classic();
+        level.name = "NO NAME".to_string();
+        let mut formation_lines = Vec::new();
+        let mut shield_lines = Vec::new();
+        // 複数行の項目を読み込み中ならばその項目名
+        let mut section: Option<&str> = None;
+        for (i, line) in text.lines().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                section = None;
+                continue;
+            }
+            // シールドの形は#から始まるため、複数行の項目の中ではコメントとみなさない
+            if section.is_none() && line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_suffix(':') {
+                match header {
+                    "formation" | "shield" => section = Some(header),
+                    _ => return Err(format!("{}行目: 不明な項目{}です。", line_num, header)),
+                }
+                continue;
+            }
+            match section {
+                Some("formation") => formation_lines.push((line_num, line)),
+                Some(_) => shield_lines.push((line_num, line)),
+                None => level.set_value(line_num, line)?,
+            }
+        }
+        if !formation_lines.is_empty() {
+            level.formation = parse_formation(&formation_lines)?;
+        }
+        if !shield_lines.is_empty() {
+            level.shield_map = parse_shield(&shield_lines)?;
+        }
+        if level.formation.iter().all(|cell| cell.is_none()) {
+            return Err("隊列にエイリアンが1匹もいません。".to_string());
+        }
+        Ok(level)
+    }
+    fn set_value(&mut self, line_num: usize, line: &str) -> Result<(), String> {
+        let (key, value) = line.split_once('=').ok_or(format!(
+            "{}行目: 「項目名=値」の形式ではありません。",
+            line_num
+        ))?;
+        let (key, value) = (key.trim(), value.trim());
+        let invalid = || format!("{}行目: {}の値{}は不正です。", line_num, key, value);
+        match key {
+            "name" => self.name = value.to_uppercase(),
+            // 一番下の段がシールドより上、隊列の一番上の段がUFOより下になる範囲
+            "start_height" => match value.parse() {
+                Ok(height @ 56..=120) if height % 8 == 0 => self.start_height = Some(height),
+                _ => return Err(invalid()),
+            },
+            "march_speed" => match value.parse() {
+                Ok(speed @ 1..=4) => self.march_speed = Some(speed),
+                _ => return Err(invalid()),
+            },
+            "bullet_speed" => match value.parse() {
+                Ok(speed @ 1..=4) => self.bullet_speed = Some(speed),
+                _ => return Err(invalid()),
+            },
+            "shield_x" => {
+                let xs = parse_list(value).ok_or_else(invalid)?;
+                if xs.len() > 8
+                    || xs
+                        .iter()
+                        .any(|x| x + SHIELD_WIDTH > canvas::GAME_WIDTH as usize)
+                {
+                    return Err(invalid());
+                }
+                self.shield_x = xs;
+            }
+            "plunger_columns" | "squiggly_columns" => {
+                let columns = parse_list(value).ok_or_else(invalid)?;
+                if columns.is_empty() || columns.iter().any(|c| !(1..=COLUMN_NUM).contains(c)) {
+                    return Err(invalid());
+                }
+                if key == "plunger_columns" {
+                    self.plunger_columns = columns;
+                } else {
+                    self.squiggly_columns = columns;
+                }
+            }
+            _ => return Err(format!("{}行目: 不明な項目{}です。", line_num, key)),
+        }
+        Ok(())
+    }
+    // レベルで指定された値でステージ数による難易度を上書きする
+    pub fn apply(&self, mut params: StageParams) -> StageParams {
+        if let Some(height) = self.start_height {
+            params.start_pos_y = canvas::GAME_HEIGHT - height;
+        }
+        if let Some(speed) = self.march_speed {
+            params.march_dx = speed;
+        }
+        if let Some(speed) = self.bullet_speed {
+            params.bullet_speed = speed;
+        }
+        params
+    }
+    // レベルファイルの文字列にする(parseで読み込める形式)
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("name={}", self.name)];
+        if let Some(height) = self.start_height {
+            lines.push(format!("start_height={}", height));
+        }
+        if let Some(speed) = self.march_speed {
+            lines.push(format!("march_speed={}", speed));
+        }
+        if let Some(speed) = self.bullet_speed {
+            lines.push(format!("bullet_speed={}", speed));
+        }
+        lines.push(format!("shield_x={}", join_list(&self.shield_x)));
+        lines.push(format!(
+            "plunger_columns={}",
+            join_list(&self.plunger_columns)
+        ));
+        lines.push(format!(
+            "squiggly_columns={}",
+            join_list(&self.squiggly_columns)
+        ));
+        lines.push(String::new());
+        lines.push("formation:".to_string());
+        for row in (0..ROW_NUM).rev() {
+            let cells = &self.formation[row * COLUMN_NUM..(row + 1) * COLUMN_NUM];
+            lines.push(
+                cells
+                    .iter()
+                    .map(|cell| match cell {
+                        Some(0) => 'O',
+                        Some(1) => 'C',
+                        Some(_) => 'S',
+                        None => '.',
+                    })
+                    .collect(),
+            );
+        }
+        lines.push(String::new());
+        lines.push("shield:".to_string());
+        for line in self.shield_map.iter() {
+            let dots: Vec<&str> = line.iter().map(|d| if *d { "#" } else { "_" }).collect();
+            lines.push(dots.join(" "));
+        }
+        lines.push(String::new());
+        lines.join("\n")
+    }
+    // シールドのスプライト(上半分、下半分の順)
+    pub fn shield_sprite(&self) -> Vec<u8> {
+        DotShape {
+            width: SHIELD_WIDTH as i32,
+            height: SHIELD_HEIGHT as i32,
+            dot_map: self.shield_map.clone(),
+        }
+        .create_dot_map()
+    }
+}
+
+// アーケード版の段(0から)ごとのエイリアンの種類
+pub fn classic_alien_type(row: usize) -> usize {
+    match row {
+        0 | 1 => 0,
+        2 | 3 => 1,
+        _ => 2,
+    }
+}
+
+// カンマ区切りの数値の並び(空の場合は空の並び)
+fn parse_list(value: &str) -> Option<Vec<usize>> {
+    if value.is_empty() {
+        return Some(Vec::new());
+    }
+    value.split(',').map(|v| v.trim().parse().ok()).collect()
+}
+
+fn join_list(values: &[usize]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    values.join(",")
+}
+
+// 上の段から順に、O(タコ)、C(カニ)、S(イカ)、.(空きマス)で11文字ずつ5段記述する
+fn parse_formation(lines: &[(usize, &str)]) -> Result<Vec<Option<usize>>, String> {
+    if lines.len() != ROW_NUM {
+        return Err(format!(
+            "{}行目: 隊列は{}段で記述してください。",
+            lines[0].0, ROW_NUM
+        ));
+    }
+    let mut formation = vec![None; COLUMN_NUM * ROW_NUM];
+    for (i, (line_num, line)) in lines.iter().enumerate() {
+        let row = ROW_NUM - 1 - i;
+        let cells: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if cells.len() != COLUMN_NUM {
+            return Err(format!(
+                "{}行目: 隊列は1段{}列で記述してください。",
+                line_num, COLUMN_NUM
+            ));
+        }
+        for (column, c) in cells.iter().enumerate() {
+            formation[row * COLUMN_NUM + column] = match c {
+                'O' => Some(0),
+                'C' => Some(1),
+                'S' => Some(2),
+                '.' => None,
+                _ => return Err(format!("{}行目: 不明なエイリアン{}です。", line_num, c)),
+            };
+        }
+    }
+    Ok(formation)
+}
+
+// スプライトと同じく、#(描画)と_(非描画)で22文字ずつ16行記述する
+fn parse_shield(lines: &[(usize, &str)]) -> Result<Vec<Vec<bool>>, String> {
+    if lines.len() != SHIELD_HEIGHT {
+        return Err(format!(
+            "{}行目: シールドは{}行で記述してください。",
+            lines[0].0, SHIELD_HEIGHT
+        ));
+    }
+    let shield_map = sprite::convert_dot_map(lines.iter().map(|(_, line)| *line).collect());
+    for (line, (line_num, _)) in shield_map.iter().zip(lines.iter()) {
+        if line.len() != SHIELD_WIDTH {
+            return Err(format!(
+                "{}行目: シールドは1行{}ドットで記述してください。",
+                line_num, SHIELD_WIDTH
+            ));
+        }
+    }
+    Ok(shield_map)
+}
+
+// キャンペーンのレベルファイルをすべて取得する(読み込めなかったファイルは飛ばす)
+pub async fn load_campaign() -> Vec<Level> {
+    let mut levels = Vec::new();
+    let list = match fetch_text(CAMPAIGN_FILE).await {
+        Some(list) => list,
+        None => return levels,
+    };
+    for filename in list.lines().map(|l| l.trim()) {
+        if filename.is_empty() || filename.starts_with('#') {
+            continue;
+        }
+        let path = format!("levels/{}", filename);
+        match fetch_text(&path).await.map(|text| Level::parse(&text)) {
+            Some(Ok(level)) => levels.push(level),
+            Some(Err(err)) => log::info!("{}: {}", path, err),
+            None => (),
+        }
+    }
+    levels
+}
+
+async fn fetch_text(resource: &str) -> Option<String> {
+    let response: Response = JsFuture::from(window()?.fetch_with_str(resource))
+        .await
+        .map_err(|err| log::info!("{}の取得に失敗しました。 {:#?}", resource, err))
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    if !response.ok() {
+        log::info!(
+            "{}の取得に失敗しました。 status: {}",
+            resource,
+            response.status()
+        );
+        return None;
+    }
+    JsFuture::from(response.text().ok()?)
+        .await
+        .ok()?
+        .as_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 各項目が一致するか比べる
+    fn assert_same(a: &Level, b: &Level) {
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.start_height, b.start_height);
+        assert_eq!(a.march_speed, b.march_speed);
+        assert_eq!(a.bullet_speed, b.bullet_speed);
+        assert_eq!(a.formation, b.formation);
+        assert_eq!(a.shield_x, b.shield_x);
+        assert_eq!(a.shield_map, b.shield_map);
+        assert_eq!(a.plunger_columns, b.plunger_columns);
+        assert_eq!(a.squiggly_columns, b.squiggly_columns);
+    }
+
+    // 不正なレベルファイルのエラー
+    fn parse_err(text: &str) -> String {
+        match Level::parse(text) {
+            Ok(_) => panic!("{}を読み込めてしまいました。", text),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn to_text_and_parse_round_trip() {
+        let mut level = Level::classic();
+        level.name = "ROUND TRIP".to_string();
+        level.start_height = Some(64);
+        level.march_speed = Some(3);
+        level.bullet_speed = Some(2);
+        level.formation[0] = None;
+        level.formation[COLUMN_NUM * ROW_NUM - 1] = Some(0);
+        level.shield_x = vec![0, 100, canvas::GAME_WIDTH as usize - SHIELD_WIDTH];
+        level.shield_map[0][0] = true;
+        level.shield_map[15][21] = false;
+        level.plunger_columns = vec![11];
+        level.squiggly_columns = vec![1, 2, 3];
+        assert_same(&Level::parse(&level.to_text()).unwrap(), &level);
+
+        // 省略した項目はアーケード版と同じ
+        let classic = Level::classic();
+        let mut parsed = Level::parse(&classic.to_text()).unwrap();
+        assert_same(&parsed, &classic);
+        parsed = Level::parse("name=empty").unwrap();
+        assert_eq!(parsed.name, "EMPTY");
+        assert_eq!(parsed.formation, classic.formation);
+        assert_eq!(parsed.shield_map, classic.shield_map);
+    }
+
+    #[test]
+    fn campaign_levels_parse() {
+        for text in [
+            include_str!("../levels/level01.txt"),
+            include_str!("../levels/level02.txt"),
+            include_str!("../levels/level03.txt"),
+            include_str!("../levels/level04.txt"),
+        ] {
+            let level = Level::parse(text).unwrap();
+            assert_same(&Level::parse(&level.to_text()).unwrap(), &level);
+        }
+    }
+
+    #[test]
+    fn formation_errors() {
+        // 列数が足りない
+        let err =
+            parse_err("formation:\nOOOOOOOOOO\nOOOOOOOOOOO\nOOOOOOOOOOO\nOOOOOOOOOOO\nOOOOOOOOOOO");
+        assert!(err.starts_with("2行目"), "{}", err);
+        // 段数が足りない
+        let err = parse_err("formation:\nOOOOOOOOOOO\nOOOOOOOOOOO");
+        assert!(err.starts_with("2行目"), "{}", err);
+        // 不明なエイリアン
+        let err = parse_err(
+            "formation:\nOOOOOOOOOOO\nOOOOOXOOOOO\nOOOOOOOOOOO\nOOOOOOOOOOO\nOOOOOOOOOOO",
+        );
+        assert!(err.starts_with("3行目") && err.contains('X'), "{}", err);
+        // エイリアンが1匹もいない
+        parse_err("formation:\n...........\n...........\n...........\n...........\n...........");
+    }
+
+    #[test]
+    fn value_errors() {
+        for text in [
+            // 範囲外、または8の倍数でない初期位置
+            "start_height=48",
+            "start_height=128",
+            "start_height=60",
+            "start_height=high",
+            // 1から11以外の列
+            "plunger_columns=0,1",
+            "squiggly_columns=12",
+            "squiggly_columns=",
+            // 画面からはみ出すシールド
+            "shield_x=203",
+            "shield_x=1,2,3,4,5,6,7,8,9",
+            "shield_x=-1",
+            "march_speed=5",
+        ] {
+            let err = parse_err(&format!("name=x\n{}", text));
+            assert!(err.starts_with("2行目"), "{}: {}", text, err);
+        }
+        // 境界の値は読み込める
+        let level = Level::parse("start_height=120\nshield_x=202\nplunger_columns=1,11").unwrap();
+        assert_eq!(level.start_height, Some(120));
+        assert_eq!(level.shield_x, [202]);
+        assert!(Level::parse("start_height=56").is_ok());
+    }
+
+    #[test]
+    fn unknown_items_are_errors() {
+        assert!(parse_err("speed=1").starts_with("1行目"));
+        assert!(parse_err("no value").starts_with("1行目"));
+        assert!(parse_err("aliens:").starts_with("1行目"));
+    }
+}
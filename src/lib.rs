@@ -0,0 +1,39 @@
+// ゲーム本体のライブラリ(ブラウザ用のGameCanvasはmain.rs)
+// examplesからはブラウザなしでゲームを動かせる
+pub mod achievement;
+pub mod alien;
+pub mod array_sprite;
+pub mod audio;
+pub mod bot;
+pub mod bottom_area;
+pub mod canvas;
+pub mod combo;
+pub mod config;
+pub mod credit;
+pub mod dot_map;
+pub mod editor;
+pub mod env;
+pub mod game;
+pub mod game_mode;
+pub mod gamepad;
+pub mod high_score;
+pub mod input;
+pub mod key_binding;
+pub mod level;
+pub mod listener;
+pub mod math;
+pub mod menu;
+pub mod mixer;
+pub mod pause;
+pub mod player;
+pub mod player_state;
+pub mod practice;
+pub mod progression;
+pub mod ruleset;
+pub mod shield;
+pub mod sprite;
+pub mod stats;
+pub mod storage;
+pub mod top_area;
+pub mod touch;
+pub mod ufo;
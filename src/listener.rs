@@ -0,0 +1,48 @@
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{Event, EventTarget};
+
+// 登録したイベントリスナー(クロージャを保持し、破棄する時に登録を解除する)
+pub struct Listener {
+    target: EventTarget,
+    event_type: &'static str,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+impl Listener {
+    // targetのevent_typeのイベントでhandlerを呼び出すように登録する(Eの型でないイベントは無視する)
+    pub fn new<E, F>(target: &EventTarget, event_type: &'static str, mut handler: F) -> Self
+    where
+        E: JsCast + 'static,
+        F: FnMut(E) + 'static,
+    {
+        let closure = Closure::wrap(Box::new(move |event: Event| {
+            if let Ok(event) = event.dyn_into::<E>() {
+                handler(event);
+            }
+        }) as Box<dyn FnMut(_)>);
+        target
+            .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())
+            .unwrap();
+        Listener {
+            target: target.clone(),
+            event_type,
+            closure,
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let result = self.target.remove_event_listener_with_callback(
+            self.event_type,
+            self.closure.as_ref().unchecked_ref(),
+        );
+        if let Err(err) = result {
+            log::info!(
+                "{}のイベントリスナーの解除に失敗しました。 {:#?}",
+                self.event_type,
+                err
+            );
+        }
+    }
+}
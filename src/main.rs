@@ -1,72 +1,140 @@
-use alien::Alien;
-use audio::Audio;
-use bottom_area::BottomArea;
-use dot_map::DotMap;
-use pause::Pause;
-use player::{Bullet, Player};
-use std::cell::RefCell;
-use std::rc::Rc;
-use top_area::TopArea;
-use ufo::Ufo;
+use invader_in_browser::achievement::Achievements;
+use invader_in_browser::array_sprite::array_text;
+use invader_in_browser::audio::{self, Audio};
+use invader_in_browser::bot::Bot;
+use invader_in_browser::bottom_area::BottomArea;
+use invader_in_browser::canvas;
+use invader_in_browser::combo::Scoring;
+use invader_in_browser::config::GameConfig;
+use invader_in_browser::credit::Credit;
+use invader_in_browser::editor;
+use invader_in_browser::game::{Game, PlayResult};
+use invader_in_browser::game_mode::{GameMode, ModeKind};
+use invader_in_browser::high_score::{HighScoreTable, InitialsEntry};
+use invader_in_browser::input;
+use invader_in_browser::key_binding::{Control, KeyBindings};
+use invader_in_browser::level::{self, Level};
+use invader_in_browser::math::Vec2;
+use invader_in_browser::menu::{Menu, MenuInput};
+use invader_in_browser::mixer::Bus;
+use invader_in_browser::pause::{Pause, PauseAction};
+use invader_in_browser::player_state::PlayerState;
+use invader_in_browser::practice::Practice;
+use invader_in_browser::shield;
+use invader_in_browser::sprite;
+use invader_in_browser::stats::{self, GameRecord, SessionHistory, StageStats};
+use invader_in_browser::storage::{LocalStorage, MemoryStorage, Storage};
+use invader_in_browser::top_area::TopArea;
+use invader_in_browser::touch::TouchButton;
 use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
 use web_sys::{window, CanvasRenderingContext2d, HtmlCanvasElement};
 use yew::prelude::*;
 
-mod alien;
-mod array_sprite;
-mod audio;
-mod bottom_area;
-mod canvas;
-mod dot_map;
-mod input;
-mod math;
-mod pause;
-mod player;
-mod sprite;
-mod top_area;
-mod ufo;
-
 #[derive(PartialEq)]
 enum Scene {
-    Title,
+    Title(i32),
+    HighScores(i32),
+    // このセッションで遊んだゲームの記録
+    History(i32),
+    // 実績のギャラリー
+    Gallery(i32),
+    EnterInitials,
+    Options,
+    PracticeSetup,
+    // 自動操縦で遊ぶデモプレイ
+    Demo(i32),
     Play,
     Pause,
     LaunchGame(i32),
+    PlayerIntro(i32),
+    // ステージの成績を表示してから次のステージへ進む
     LaunchStage(i32),
     ResetStage,
     Gameover(i32),
+    // ゲームオーバーになったプレイヤーのゲーム全体の成績
+    GameSummary(i32),
 }
 
 pub enum Msg {
     RetAudio,
     RegisterAudio(Audio),
+    RegisterLevels(Vec<Level>),
+    PlayLevel(Level),
     AudioVolumeUp,
     AudioVolumeDown,
     AudioVolumeReset,
+    InsertCoin,
+    PushStart(i32),
+    // タッチ操作のボタンが押された(真)、離された(偽)
+    Touch(TouchButton, bool),
+    // ウィンドウのフォーカスやタブの表示状態が変わった(戻った時に真)
+    FocusChanged(bool),
     ResetCanvas,
     Initialize,
     MainLoop,
 }
 
+// タイトル画面とハイスコア表を切り替えるまでのカウント
+const ATTRACT_CNT: i32 = 600;
+// ステージの成績とゲーム全体の成績を表示するtick数
+const STAGE_RESULTS_CNT: i32 = 240;
+const GAME_SUMMARY_CNT: i32 = 300;
+// デモプレイを続ける最大tick数
+const DEMO_CNT: i32 = 3600;
+
 struct GameCanvas {
     canvas: NodeRef,
     tmp_canvas: HtmlCanvasElement,
-    map: DotMap,
     top: TopArea,
     bottom: BottomArea,
-    player: Player,
-    player_bullet: Bullet,
-    alien: Alien,
-    alien_bullets: alien::BulletManage,
-    ufo: Ufo,
-    shield: Vec<u8>,
-    stage: usize, // 最初は1、最終は9
+    // ゲーム本体
+    game: Game,
+    // 文字のスプライトのリスト
+    char_list: Vec<Vec<u8>>,
+    // 2人プレイならば真
+    two_players: bool,
+    // 操作中のプレイヤー(0: 1P, 1: 2P)
+    active_player: usize,
+    // 各プレイヤーの状態(操作中のプレイヤーの分は交代時に更新する)
+    player_states: [PlayerState; 2],
+    // ハイスコア表などの保存先
+    storage: Box<dyn Storage>,
+    // 設定画面などのメニュー
+    menu: Menu,
+    credit: Credit,
+    // 画面下の開始ボタンで押された人数(押されていなければNone)
+    pushed_start: Option<i32>,
+    // ルールと得点の計算方法ごとのハイスコア表
+    high_scores: Vec<HighScoreTable>,
+    // 次に表示するハイスコア表(high_scoresの位置)
+    shown_high_scores: usize,
+    // タイトル画面で選んだルール
+    selected_mode: ModeKind,
+    // 練習モードの設定画面で選んだ設定
+    practice_setup: Practice,
+    // イニシャル入力中の情報
+    initials: Option<InitialsEntry>,
+    // これからイニシャルを入力する(プレイヤー, 得点)
+    pending_initials: Vec<(usize, i32)>,
     audio: Audio,
     callback: Closure<dyn FnMut()>,
-    input_key: Rc<RefCell<input::KeyDown>>,
-    // 真の場合、画面全体を赤色にする
-    player_exploding: bool,
+    // キーボード、ゲームパッド、タッチ操作の入力をtickごとに読み取ったもの
+    actions: input::Actions,
+    // キーボード、ゲームパッド、タッチ操作の入力(イベントリスナーを保持する)
+    input: input::InputManager,
+    // デモプレイでプレイヤーを操作する自動操縦
+    demo_bot: Bot,
+    // デモプレイの自動操縦の入力
+    demo_actions: input::Actions,
+    // 次のデモプレイの自動操縦のシード
+    demo_seed: u64,
     pause: Pause,
+    // このセッションで遊んだゲームの記録
+    session_history: SessionHistory,
+    // 解除済みの実績
+    achievements: Achievements,
+    // ウィンドウのフォーカスを失っている間は真
+    focus_lost: bool,
     scene: Scene,
 }
 
@@ -75,63 +143,16 @@ impl Component for GameCanvas {
     type Message = Msg;
     fn create(ctx: &Context<Self>) -> Self {
         let player_data = sprite::ret_dot_data("player");
-        let bullet_player_data = sprite::ret_dot_data("bullet_player");
-        if bullet_player_data.width != 1 {
-            panic!("プレイヤーの弾の幅は1以外は不正です。");
-        }
-        let player_explosion_1_data = sprite::ret_dot_data("player_explosion_1");
-        let player_explosion_2_data = sprite::ret_dot_data("player_explosion_2");
-        let player_bullet_explosion_data = sprite::ret_dot_data("player_bullet_explosion");
-        let ufo_data = sprite::ret_dot_data("ufo");
-        let ufo_explosion_data = sprite::ret_dot_data("ufo_explosion");
-        let shield_data = sprite::ret_dot_data("shield");
-        let octopus_open_data = sprite::ret_dot_data("octopus_open");
-        let octopus_close_data = sprite::ret_dot_data("octopus_close");
-        let crab_banzai_data = sprite::ret_dot_data("crab_banzai");
-        let crab_down_data = sprite::ret_dot_data("crab_down");
-        let squid_open_data = sprite::ret_dot_data("squid_open");
-        let squid_close_data = sprite::ret_dot_data("squid_close");
-        let alien_explosion_data = sprite::ret_dot_data("alien_explosion");
-        let alien_bullet_explosion_data = sprite::ret_dot_data("alien_bullet_explosion");
         let num_data = sprite::char_dot_data();
 
         // 各構造体初期化
         let player_sprite = player_data.create_dot_map();
 
-        let num_list: Vec<Vec<u8>> = num_data.iter().map(|n| n.create_dot_map()).collect();
+        let char_list: Vec<Vec<u8>> = num_data.iter().map(|n| n.create_dot_map()).collect();
         // 画面上部
-        let top = top_area::TopArea::new(num_list.clone());
-        // メインのゲーム画面
-        let map = DotMap::new();
+        let top = TopArea::new(char_list.clone());
         // 画面下部
-        let bottom = bottom_area::BottomArea::new(num_list.clone(), player_sprite.clone());
-        let player = Player::new(
-            player_sprite.clone(),
-            player_explosion_1_data.create_dot_map(),
-            player_explosion_2_data.create_dot_map(),
-        );
-        let player_bullet = Bullet::new(
-            bullet_player_data.create_dot_map(),
-            player_bullet_explosion_data.create_dot_map(),
-        );
-
-        let ufo = Ufo::new(
-            ufo_data.create_dot_map(),
-            ufo_explosion_data.create_dot_map(),
-            num_list.clone(),
-        );
-        let shield = shield_data.create_dot_map();
-
-        let alien = Alien::new(
-            octopus_open_data.create_dot_map(),
-            octopus_close_data.create_dot_map(),
-            crab_banzai_data.create_dot_map(),
-            crab_down_data.create_dot_map(),
-            squid_open_data.create_dot_map(),
-            squid_close_data.create_dot_map(),
-            alien_explosion_data.create_dot_map(),
-        );
-        let alien_bullets = alien::BulletManage::new(alien_bullet_explosion_data.create_dot_map());
+        let bottom = BottomArea::new(char_list.clone(), player_sprite);
 
         let tmp_canvas: HtmlCanvasElement = window()
             .unwrap()
@@ -157,6 +178,17 @@ impl Component for GameCanvas {
         //     .unwrap()
         //     .unchecked_into();
 
+        // localStorageが利用できない場合はメモリ上に保存する(再読み込みで消える)
+        let storage: Box<dyn Storage> = match LocalStorage::new() {
+            Some(storage) => Box::new(storage),
+            None => Box::new(MemoryStorage::default()),
+        };
+        let high_scores = HighScoreTable::load_all(storage.as_ref());
+        // メインのゲーム画面
+        let game = Game::new(GameConfig::load(storage.as_ref()));
+        let achievements = Achievements::load(storage.as_ref());
+        let input = input::InputManager::new(KeyBindings::load(storage.as_ref()));
+
         let comp_ctx = ctx.link().clone();
         let callback = Closure::wrap(
             Box::new(move || comp_ctx.send_message(Msg::MainLoop)) as Box<dyn FnMut()>
@@ -166,25 +198,36 @@ impl Component for GameCanvas {
             canvas: NodeRef::default().clone(),
             tmp_canvas,
             top,
-            map,
             bottom,
-            player,
-            player_bullet,
-            alien,
-            alien_bullets,
-            shield,
-            ufo,
+            game,
             audio: Audio::new(),
-            input_key: Rc::new(RefCell::new(input::KeyDown {
-                left: false,
-                right: false,
-                shot: false,
-                pause: false,
-            })),
-            stage: 1,
-            player_exploding: false,
-            scene: Scene::Title,
+            actions: input::Actions::default(),
+            input,
+            char_list,
+            two_players: false,
+            active_player: 0,
+            player_states: [
+                PlayerState::new(0, 0, false, 1),
+                PlayerState::new(0, 0, false, 1),
+            ],
+            demo_bot: Bot::new(0),
+            demo_actions: input::Actions::default(),
+            demo_seed: 0,
+            storage,
+            menu: Menu::new(1),
+            credit: Credit::new(),
+            pushed_start: None,
+            high_scores,
+            shown_high_scores: 0,
+            selected_mode: ModeKind::Classic,
+            practice_setup: Practice::default(),
+            initials: None,
+            pending_initials: Vec::new(),
+            focus_lost: false,
+            scene: Scene::Title(ATTRACT_CNT),
             pause: Pause::new(),
+            session_history: SessionHistory::default(),
+            achievements,
             callback,
         }
     }
@@ -203,13 +246,13 @@ impl Component for GameCanvas {
             }
             // 初期化
             Msg::Initialize => {
-                // エイリアンの初期化
-                self.alien.reset(self.stage);
-                // プレイヤーの初期化
-                self.player.reset_all();
-                self.ufo.reset();
                 // キー入力情報初期化
-                input::input_setup(&self.input_key);
+                let on_focus_change = ctx.link().callback(Msg::FocusChanged);
+                self.input
+                    .setup(move |focused| on_focus_change.emit(focused));
+                // キャンペーンのレベルファイルを取得
+                ctx.link()
+                    .send_future(async { Msg::RegisterLevels(level::load_campaign().await) });
 
                 ctx.link().send_message(Msg::RetAudio);
                 true
@@ -222,22 +265,82 @@ impl Component for GameCanvas {
                 }
                 false
             }
-            // 音データを保存
-            Msg::RegisterAudio(audio) => {
+            // 音データを保存(読み込み中に変えた音量とミュートは引き継ぐ)
+            Msg::RegisterAudio(mut audio) => {
+                audio.mixer.copy_levels_from(&self.audio.mixer);
+                // 読み込み中にフォーカスを失っていたら、新しく作ったAudioContextも止める
+                if self.focus_lost {
+                    audio.suspend();
+                }
                 self.audio = audio;
                 ctx.link().send_message(Msg::MainLoop);
                 false
             }
+            // キャンペーンのレベルを保存(1つも読み込めなければアーケード版のレベルのみ)
+            Msg::RegisterLevels(levels) => {
+                if !levels.is_empty() {
+                    self.game.campaign = levels;
+                }
+                false
+            }
+            // レベルエディタで作ったレベルをすぐに遊ぶ(クレジットを消費しない)
+            Msg::PlayLevel(level) => {
+                // 遊んでいるゲームや入力中のイニシャルを捨てないように、タイトル画面とデモプレイ以外では受け付けない
+                if !self.in_attract() {
+                    log::info!("ゲーム中はレベルを試遊できません。");
+                    return false;
+                }
+                self.game.mode = GameMode::Custom {
+                    level,
+                    cleared: false,
+                };
+                self.two_players = false;
+                self.launch_game();
+                false
+            }
             Msg::AudioVolumeUp => {
-                self.audio.all_volume_up();
+                self.audio.mixer.change_level(Bus::Master, 1);
                 false
             }
             Msg::AudioVolumeDown => {
-                self.audio.all_volume_down();
+                self.audio.mixer.change_level(Bus::Master, -1);
                 false
             }
             Msg::AudioVolumeReset => {
-                self.audio.reset_volume();
+                self.audio.mixer.reset();
+                false
+            }
+            Msg::InsertCoin => {
+                self.credit.insert_coin();
+                false
+            }
+            Msg::PushStart(player_num) => {
+                // タイトル画面とデモプレイ以外では受け付けない
+                if self.in_attract() {
+                    self.pushed_start = Some(player_num);
+                }
+                false
+            }
+            Msg::Touch(button, pressed) => {
+                self.input.touch.set(button, pressed);
+                false
+            }
+            // フォーカスを失ったら入力を離し、音を止め、ゲーム中ならばポーズする
+            // タブが隠れている間はメインループが止まるので、ここで直接処理する
+            // ステージ開始前などに失った場合は、ゲームが始まった時にポーズする
+            Msg::FocusChanged(focused) => {
+                self.focus_lost = !focused;
+                if !focused {
+                    self.input.release_all();
+                    self.audio.suspend();
+                    if self.scene == Scene::Play {
+                        self.scene = Scene::Pause;
+                        self.pause.open();
+                    }
+                } else if self.scene != Scene::Pause {
+                    // ポーズ中はポーズを解除するまで音を止めたままにする
+                    self.audio.resume();
+                }
                 false
             }
             // ループ
@@ -248,9 +351,17 @@ impl Component for GameCanvas {
         }
     }
 
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        // キー入力などのイベントリスナーの登録を解除する
+        self.input.teardown();
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
         let canvas_width = (canvas::ALL_WIDTH * canvas::SCALE).to_string();
         let canvas_height = (canvas::ALL_HEIGHT * canvas::SCALE).to_string();
+        let editor_props = editor::Props {
+            on_play: ctx.link().callback(Msg::PlayLevel),
+        };
         html! {
             <div>
                 <div id="parent-audio-button">
@@ -262,18 +373,85 @@ impl Component for GameCanvas {
                     width={canvas_width}
                     height={canvas_height}
                     ref={self.canvas.clone()}/>
+                { self.view_touch_controls(ctx) }
+                <div class="coin-buttons-list">
+                    <button class="coin-button" onclick={ctx.link().callback(|_| Msg::InsertCoin)}>{ "Insert Coin" }</button>
+                    <button class="coin-button" onclick={ctx.link().callback(|_| Msg::PushStart(1))}>{ "1P Start" }</button>
+                    <button class="coin-button" onclick={ctx.link().callback(|_| Msg::PushStart(2))}>{ "2P Start" }</button>
+                </div>
                 <div class="volume-buttons-list">
                     <button class="volume-button" onclick={ctx.link().callback(|_| Msg::AudioVolumeUp)}>{ "Volume Up" }</button>
                     <button class="volume-button" onclick={ctx.link().callback(|_| Msg::AudioVolumeReset)}>{ "Reset Volume" }</button>
                     <button class="volume-button" onclick={ctx.link().callback(|_| Msg::AudioVolumeDown)}>{ "Volume Down" }</button>
                 </div>
+                <editor::LevelEditor ..editor_props />
             </div>
         }
     }
 }
 
 impl GameCanvas {
+    // タッチ操作ができる端末ではキャンバスの下にタッチ操作のボタンを表示する
+    fn view_touch_controls(&self, ctx: &Context<Self>) -> Html {
+        if !self.input.touch.enabled {
+            return html! {};
+        }
+        let button = |button: TouchButton| {
+            let class = match button {
+                TouchButton::Left | TouchButton::Right => "touch-button touch-move",
+                TouchButton::Up | TouchButton::Down => "touch-button touch-menu",
+                TouchButton::Fire => "touch-button touch-fire",
+                TouchButton::Pause => "touch-button touch-pause",
+            };
+            // スクロールや拡大はCSSのtouch-actionで止め、ここではマウスイベントの発生を止める
+            // yewはtouchstartをpassiveで登録するので、prevent_defaultが効くように明示的にpassiveにしない
+            let on_press = ctx
+                .link()
+                .callback_with_passive(false, move |e: TouchEvent| {
+                    e.prevent_default();
+                    Msg::Touch(button, true)
+                });
+            let on_release = ctx
+                .link()
+                .callback_with_passive(false, move |e: TouchEvent| {
+                    e.prevent_default();
+                    Msg::Touch(button, false)
+                });
+            let on_cancel = on_release.clone();
+            html! {
+                <button
+                    class={class}
+                    ontouchstart={on_press}
+                    ontouchend={on_release}
+                    ontouchcancel={on_cancel}>
+                    { button.label() }
+                </button>
+            }
+        };
+        // キャンバスと同じ幅にする
+        let style = format!("width: {}px;", canvas::ALL_WIDTH * canvas::SCALE);
+        html! {
+            <div class="touch-controls" style={style}>
+                <div class="touch-group">
+                    { button(TouchButton::Left) }
+                    { button(TouchButton::Right) }
+                </div>
+                <div class="touch-group touch-menu-group">
+                    { button(TouchButton::Up) }
+                    { button(TouchButton::Down) }
+                </div>
+                <div class="touch-group">
+                    { button(TouchButton::Pause) }
+                    { button(TouchButton::Fire) }
+                </div>
+            </div>
+        }
+    }
     fn main_loop(&mut self) {
+        // キーボード、ゲームパッド、タッチ操作の入力を読み取る
+        let dead_zone = self.game.config.dead_zone as f64 / 100.;
+        self.actions.sample(&self.input.read(dead_zone));
+
         let canvas: HtmlCanvasElement = self.canvas.cast().unwrap();
         let ctx: CanvasRenderingContext2d =
             canvas.get_context("2d").unwrap().unwrap().unchecked_into();
@@ -284,9 +462,18 @@ impl GameCanvas {
         ctx.set_fill_style(&JsValue::from("rgb(0,0,0)"));
         ctx.fill_rect(0.0, 0.0, canvas.width().into(), canvas.height().into());
 
-        let (game_imagedata, _game_unused) =
-            self.map
-                .dot_map2imagedata(&self.top.top, &self.bottom.bottom, self.player_exploding);
+        // ポーズ中はゲーム画面の代わりにポーズメニューを表示する
+        let (game_imagedata, _game_unused) = if self.scene == Scene::Pause {
+            self.pause
+                .map
+                .dot_map2imagedata(&self.top.top, &self.bottom.bottom, false)
+        } else {
+            self.game.map.dot_map2imagedata(
+                &self.top.top,
+                &self.bottom.bottom,
+                self.game.player_exploding,
+            )
+        };
         // オリジナルサイズ(scale = 1)のキャンバス
         let tmp_ctx: CanvasRenderingContext2d = self
             .tmp_canvas
@@ -307,20 +494,84 @@ impl GameCanvas {
         )
         .unwrap();
 
-        // 得点表示
-        self.top.draw_score(self.player_bullet.score);
+        // 得点表示(プレイヤー紹介中は操作するプレイヤーの得点を点滅させる)
+        let hide_active = matches!(self.scene, Scene::PlayerIntro(cnt) if cnt / 8 % 2 == 0);
+        self.top.draw_score(
+            self.scores(),
+            self.hi_score(),
+            self.active_player,
+            hide_active,
+        );
+        // ゲーム中はステージ数を表示
+        let in_game = matches!(
+            self.scene,
+            Scene::Play
+                | Scene::Demo(_)
+                | Scene::Pause
+                | Scene::PlayerIntro(_)
+                | Scene::LaunchStage(_)
+                | Scene::ResetStage
+                | Scene::Gameover(_)
+        );
+        self.top.clear_status_row();
+        self.top
+            .draw_stage(if in_game { Some(self.game.stage) } else { None });
+        // モダンの得点方式ではゲーム中に倍率を表示
+        self.top
+            .draw_combo(if in_game && self.game.config.scoring == Scoring::Modern {
+                Some(self.game.combo.multiplier())
+            } else {
+                None
+            });
+        // スコアアタック、タイムアタック中はタイマーを表示
+        self.top.draw_timer(if in_game {
+            self.game.mode.timer_text()
+        } else {
+            None
+        });
+        // 実績を解除したらしばらく知らせる
+        if let Some(text) = self.achievements.toast() {
+            self.top.draw_toast(&text);
+        }
         // 残機表示
-        self.bottom.draw(self.player.life);
+        // コイン投入
+        if self.actions.just_pressed(Control::Coin) {
+            self.credit.insert_coin();
+        }
+        let credits = if self.game.config.free_play {
+            None
+        } else {
+            Some(self.credit.credits)
+        };
+        self.bottom.draw(self.game.player.life, credits);
 
         match self.scene {
-            Scene::Title => {
-                // ショットボタンが押されたら
-                if self.input_key.borrow().shot {
-                    self.scene = Scene::LaunchGame(10);
-                    // 前回のドットマップをすべて消す
-                    self.top.all_clear();
-                    self.map.all_clear();
-                    self.bottom.all_clear();
+            Scene::Title(cnt) => {
+                // ルール切り替えキーが押された瞬間に次のルールを選ぶ
+                if self.actions.just_pressed(Control::Mode) {
+                    self.selected_mode = self.selected_mode.next();
+                }
+                // 一定時間経過したらハイスコア表を表示する
+                if self.actions.just_pressed(Control::Options) {
+                    // 設定画面を開く
+                    self.scene = Scene::Options;
+                    self.menu = Menu::new(GameConfig::ITEM_NUM + 1);
+                } else if self.actions.just_pressed(Control::History) {
+                    // このセッションで遊んだゲームの記録を表示する
+                    self.scene = Scene::History(ATTRACT_CNT);
+                } else if self.actions.just_pressed(Control::Gallery) {
+                    // 実績のギャラリー画面を表示する
+                    self.scene = Scene::Gallery(ATTRACT_CNT);
+                } else if self.actions.just_pressed(Control::Practice) {
+                    // 練習モードの設定画面を開く
+                    self.scene = Scene::PracticeSetup;
+                    self.menu = Menu::new(Practice::ITEM_NUM + 1);
+                } else if !self.check_start() {
+                    self.scene = if cnt < 0 {
+                        Scene::HighScores(ATTRACT_CNT)
+                    } else {
+                        Scene::Title(cnt - 1)
+                    };
                 }
                 // 画像のぼやけを防ぐ
                 ctx.set_image_smoothing_enabled(false);
@@ -328,139 +579,291 @@ impl GameCanvas {
                 ctx.set_global_alpha(1.);
                 ctx.set_fill_style(&JsValue::from("rgb(0,0,0)"));
                 ctx.fill_rect(0.0, 0.0, canvas.width().into(), canvas.height().into());
-                draw_title(&ctx);
+                draw_title(
+                    &ctx,
+                    self.game.config.show_coin_info,
+                    credits,
+                    self.selected_mode,
+                );
+            }
+            Scene::HighScores(cnt) => {
+                // 一定時間経過したらデモプレイを始める
+                if !self.check_start() {
+                    self.game.map.all_clear();
+                    self.high_scores[self.shown_high_scores]
+                        .draw(&mut self.game.map, &self.char_list);
+                    if cnt < 0 {
+                        // 次回は別の表を表示する
+                        self.next_high_scores();
+                        // 遊んだゲームがあればその記録を表示する
+                        if self.session_history.games.is_empty() {
+                            self.start_demo();
+                        } else {
+                            self.scene = Scene::History(ATTRACT_CNT);
+                        }
+                    } else {
+                        self.scene = Scene::HighScores(cnt - 1);
+                    }
+                }
+            }
+            Scene::History(cnt) => {
+                // 一定時間経過したらデモプレイを始める
+                if !self.check_start() {
+                    self.game.map.all_clear();
+                    self.session_history
+                        .draw(&mut self.game.map, &self.char_list);
+                    if cnt < 0 {
+                        self.start_demo();
+                    } else {
+                        self.scene = Scene::History(cnt - 1);
+                    }
+                }
+            }
+            Scene::Gallery(cnt) => {
+                // 一定時間経過したらデモプレイを始める
+                if !self.check_start() {
+                    self.game.map.all_clear();
+                    self.achievements.draw(&mut self.game.map, &self.char_list);
+                    if cnt < 0 {
+                        self.start_demo();
+                    } else {
+                        self.scene = Scene::Gallery(cnt - 1);
+                    }
+                }
+            }
+            Scene::Demo(cnt) => {
+                // 自動操縦でプレイし、一定時間経過するかゲームオーバーになったらタイトル画面に戻る
+                if !self.check_start() {
+                    self.demo_actions.sample(&self.demo_bot.think(&self.game));
+                    let result = self.game.update(&self.demo_actions, &self.audio);
+                    match result {
+                        PlayResult::StageCleared => {
+                            self.game.next_stage();
+                            self.game.reset_stage(None, None);
+                        }
+                        PlayResult::GameOver => self.return_to_title(),
+                        _ if cnt < 0 => self.return_to_title(),
+                        _ => self.scene = Scene::Demo(cnt - 1),
+                    }
+                    if matches!(self.scene, Scene::Demo(_)) {
+                        draw_demo(&ctx);
+                    }
+                }
+            }
+            Scene::EnterInitials => {
+                self.game.map.all_clear();
+                if let Some(entry) = self.initials.as_mut() {
+                    entry.update(&self.actions);
+                    entry.draw(&mut self.game.map, &self.char_list);
+                    // 3文字入力し終えたら表に登録して保存する
+                    if entry.is_done() {
+                        let table = self
+                            .high_scores
+                            .iter_mut()
+                            .find(|table| table.is_for(entry.kind, entry.scoring))
+                            .unwrap();
+                        table.insert(&entry.name(), entry.score);
+                        table.save(self.storage.as_mut());
+                        self.next_initials();
+                    }
+                } else {
+                    self.next_initials();
+                }
+            }
+            Scene::Options => {
+                let cursor = self.menu.cursor;
+                let exit = match self.menu.update(&self.actions) {
+                    input @ (MenuInput::Left | MenuInput::Right)
+                        if cursor < GameConfig::ITEM_NUM =>
+                    {
+                        self.game
+                            .config
+                            .change_item(cursor, input == MenuInput::Right);
+                        false
+                    }
+                    MenuInput::Select => cursor == GameConfig::ITEM_NUM,
+                    MenuInput::Back => true,
+                    _ => false,
+                };
+                // 設定を保存してタイトル画面に戻る
+                if exit {
+                    self.game.config.save(self.storage.as_mut());
+                    self.scene = Scene::Title(ATTRACT_CNT);
+                }
+                let mut items: Vec<String> = (0..GameConfig::ITEM_NUM)
+                    .map(|i| self.game.config.item_text(i))
+                    .collect();
+                items.push("SAVE AND EXIT".to_string());
+                self.game.map.all_clear();
+                self.menu
+                    .draw(&mut self.game.map, &self.char_list, "OPTIONS", &items);
+            }
+            Scene::PracticeSetup => {
+                let cursor = self.menu.cursor;
+                let input = self.menu.update(&self.actions);
+                match input {
+                    MenuInput::Left | MenuInput::Right if cursor < Practice::ITEM_NUM => {
+                        self.practice_setup
+                            .change_item(cursor, input == MenuInput::Right);
+                    }
+                    // 練習モードはクレジットを消費せずに開始する
+                    MenuInput::Select if cursor == Practice::ITEM_NUM => {
+                        self.game.mode = GameMode::Practice(self.practice_setup.clone());
+                        self.two_players = false;
+                        self.launch_game();
+                    }
+                    MenuInput::Back => self.scene = Scene::Title(ATTRACT_CNT),
+                    _ => (),
+                }
+                if self.scene == Scene::PracticeSetup {
+                    let mut items: Vec<String> = (0..Practice::ITEM_NUM)
+                        .map(|i| self.practice_setup.item_text(i))
+                        .collect();
+                    items.push("START".to_string());
+                    self.game.map.all_clear();
+                    self.menu
+                        .draw(&mut self.game.map, &self.char_list, "PRACTICE", &items);
+                }
             }
             Scene::Play => {
-                // Escキーが押されていたらポーズ
-                if self.pause.toggle_pause(self.input_key.borrow().pause) {
+                // ポーズボタンが押された瞬間か、フォーカスを失っている間はポーズ
+                if self.actions.just_pressed(Control::Pause) || self.focus_lost {
                     self.scene = Scene::Pause;
+                    self.pause.open();
                 }
                 // 更新処理
-                self.ufo.update(
-                    &mut self.map,
-                    self.player_bullet.fire_cnt,
-                    self.alien.live_num,
-                    &self.audio,
-                );
-
-                self.alien
-                    .update(&mut self.map, self.player_exploding, &self.audio);
-                self.alien_bullets.update(
-                    &mut self.map,
-                    &mut self.player,
-                    &mut self.alien,
-                    self.player_bullet.score,
-                    &self.audio,
-                );
-
-                self.player
-                    .update(&mut self.map, &mut self.input_key.borrow());
-                self.player_bullet.update(
-                    &mut self.map,
-                    &mut self.player,
-                    &mut self.ufo,
-                    &mut self.alien,
-                    &self.input_key.borrow(),
-                    &self.audio,
-                );
-
-                // エイリアンが全滅したら
-                if self.alien.live_num <= 0 {
-                    // 次のステージへ進む
-                    self.scene = Scene::LaunchStage(120);
+                let result = self.game.update(&self.actions, &self.audio);
+                // 練習モードとレベルエディタの試遊では実績を解除しない
+                if self.achievements.check(&self.game) {
+                    self.achievements.save(self.storage.as_mut());
                 }
-                // プレイヤーの残機が0またはエイリアンがプレイヤーの高さまで侵攻したら
-                if self.player.life <= 0 || self.alien.invaded() {
-                    // ゲームオーバー
-                    self.scene = Scene::Gameover(120);
-                    // 音を止める
-                    self.ufo.reset();
-                    if self.alien.invaded() {
-                        // プレイヤーの高さに降りてきた個体を描く
-                        self.alien
-                            .update(&mut self.map, self.player_exploding, &self.audio);
-                        // エイリアンに侵攻されていたら爆発を起こす
-                        self.player.remove(&mut self.map, &self.audio);
-                    };
+                match result {
+                    PlayResult::StageCleared => self.show_stage_results(),
+                    PlayResult::GameOver => self.scene = Scene::Gameover(120),
+                    // 2人プレイ時、撃破されたプレイヤーが復活したら交代する
+                    PlayResult::LifeLost if self.two_players => self.change_player(),
+                    _ => (),
                 }
-                // プレイヤーが爆発中は画面全体を赤にする
-                self.player_exploding = if self.player.explosion_cnt == None {
-                    false
-                } else {
-                    true
-                };
             }
             Scene::ResetStage => {
                 // ゲーム開始、ステージ開始時共通
                 self.scene = Scene::Play;
-                // すべて消す
-                self.map.all_clear();
                 self.top.all_clear();
                 self.bottom.all_clear();
-                // プレイヤーの下の横線
-                self.map.draw_holizon_line(canvas::GAME_HEIGHT - 1);
-                // シールド配置
-                let shield_width = self.shield.len() / 2;
-                for i in 0..4 {
-                    let gap = (shield_width + 23) * i;
-                    for dx in 0..shield_width {
-                        self.map.map[20][gap + 33 + dx] = self.shield[dx];
-                    }
-                    for dx in 0..shield_width {
-                        self.map.map[21][gap + 33 + dx] = self.shield[shield_width + dx];
-                    }
-                }
-                self.alien.reset(self.stage);
-                self.alien_bullets.reset();
-                self.ufo.reset();
+                // 交代前に削られたシールドや隊列があれば再開する
+                let state = &mut self.player_states[self.active_player];
+                let shield_rows = state.shield.take();
+                let formation = state.formation.take();
+                self.game.reset_stage(shield_rows, formation);
             }
             Scene::LaunchGame(cnt) => {
                 // 一定時間経過したらゲーム開始
                 if cnt < 0 {
-                    self.scene = Scene::ResetStage;
-
-                    self.stage = 1;
-                    self.player.reset_all();
-                    self.player_bullet.reset_all();
+                    let stage = self.game.mode.practice().map_or(1, |p| p.stage);
+                    self.game.start(stage);
+                    // 両プレイヤーの状態を初期化する
+                    self.active_player = 0;
+                    self.player_states = [
+                        PlayerState::new(0, self.game.player.life, false, self.game.stage),
+                        PlayerState::new(0, self.game.player.life, false, self.game.stage),
+                    ];
+                    self.start_player_intro();
                 } else {
                     self.scene = Scene::LaunchGame(cnt - 1);
                 }
             }
+            Scene::PlayerIntro(cnt) => {
+                // 一定時間経過したらステージ開始
+                if cnt < 0 {
+                    self.scene = Scene::ResetStage;
+                } else {
+                    self.scene = Scene::PlayerIntro(cnt - 1);
+                }
+            }
             Scene::LaunchStage(cnt) => {
                 // 一定時間経過したら次のステージ開始
                 if cnt < 0 {
                     self.scene = Scene::ResetStage;
-
-                    self.stage += 1;
-                    self.player.reset_stage();
-                    self.player_bullet.reset_stage();
+                    self.game.next_stage();
                 } else {
                     self.scene = Scene::LaunchStage(cnt - 1);
                 }
             }
             Scene::Gameover(cnt) => {
-                // 一定時間経過したらタイトル画面に戻る
+                // 一定時間経過したらゲーム全体の成績を表示する
                 if cnt < 0 {
-                    self.scene = Scene::Title;
-                    self.player_bullet.score = 0;
+                    self.show_game_summary();
                 } else {
                     self.scene = Scene::Gameover(cnt - 1);
                     // プレイヤーを爆発させる
-                    if let Some(cnt) = self.player.explosion_cnt {
-                        if cnt <= self.player.const_max_explosion_cnt {
-                            self.player
-                                .update(&mut self.map, &mut self.input_key.borrow());
+                    if let Some(cnt) = self.game.player.explosion_cnt {
+                        if cnt <= self.game.player.const_max_explosion_cnt {
+                            self.game.player.update(&mut self.game.map, &self.actions);
                         }
                     }
                 }
-                draw_gameover_message(&ctx);
+                let player_num = if self.two_players {
+                    Some(self.active_player + 1)
+                } else {
+                    None
+                };
+                draw_gameover_message(&ctx, player_num, self.game.mode.is_cleared());
+            }
+            Scene::GameSummary(cnt) => {
+                // 一定時間経過したらタイトル画面に戻る
+                if cnt < 0 {
+                    let next = 1 - self.active_player;
+                    if self.two_players && 0 < self.player_states[next].life {
+                        // もう一方のプレイヤーが残っていたら交代する
+                        self.game.player.life = 0;
+                        self.change_player();
+                    } else {
+                        self.finish_game();
+                    }
+                } else {
+                    self.scene = Scene::GameSummary(cnt - 1);
+                }
             }
             Scene::Pause => {
-                // Escキーが押されていたらポーズ解除
-                if self.pause.toggle_pause(self.input_key.borrow().pause) {
-                    self.scene = Scene::Play;
+                // ポーズメニューの操作(ゲームの状態は選んだ操作を実行するまで変えない)
+                let action = self.pause.update(
+                    &self.actions,
+                    &mut self.input.keyboard.borrow_mut(),
+                    &mut self.audio,
+                );
+                // ポーズを抜ける時に、フォーカスを失って止めた音を再開する
+                if !self.focus_lost
+                    && matches!(
+                        action,
+                        PauseAction::Resume | PauseAction::RestartStage | PauseAction::Quit
+                    )
+                {
+                    self.audio.resume();
+                }
+                match action {
+                    PauseAction::Resume => self.scene = Scene::Play,
+                    PauseAction::RestartStage => {
+                        // 残機と得点はそのままで、現在のステージを最初から始める
+                        self.game.player.reset_stage();
+                        self.game.player_bullet.reset_stage();
+                        self.scene = Scene::ResetStage;
+                    }
+                    PauseAction::Quit => self.return_to_title(),
+                    PauseAction::BindingsChanged | PauseAction::None => {
+                        if action == PauseAction::BindingsChanged {
+                            self.input
+                                .keyboard
+                                .borrow()
+                                .bindings
+                                .save(self.storage.as_mut());
+                        }
+                        self.pause.draw(
+                            &self.char_list,
+                            &self.audio,
+                            &self.input.keyboard.borrow().bindings,
+                        );
+                    }
                 }
-                draw_pause(&ctx);
             }
         }
 
@@ -469,6 +872,243 @@ impl GameCanvas {
             .request_animation_frame(self.callback.as_ref().unchecked_ref())
             .unwrap();
     }
+    // 開始ボタンが押され、クレジットが足りていたらゲームを開始して真を返す
+    // 1キーで1人プレイ、2キーで2人プレイを開始(フリープレイ時はショットボタンでも1人プレイを開始)
+    fn check_start(&mut self) -> bool {
+        // 2人プレイはクラシックのみ
+        let player_num = if self.actions.just_pressed(Control::Start2)
+            && self.selected_mode == ModeKind::Classic
+        {
+            Some(2)
+        } else if self.actions.just_pressed(Control::Start1)
+            || (self.game.config.free_play && self.actions.just_pressed(Control::Shot))
+        {
+            Some(1)
+        } else {
+            None
+        };
+        let player_num = self
+            .pushed_start
+            .take()
+            .filter(|num| *num == 1 || self.selected_mode == ModeKind::Classic)
+            .or(player_num);
+        let start = match player_num {
+            Some(num) => self.credit.consume(num, self.game.config.free_play),
+            None => false,
+        };
+        if start {
+            self.two_players = player_num == Some(2);
+            self.game.mode = GameMode::new(self.selected_mode, self.game.campaign.len());
+            self.launch_game();
+        }
+        start
+    }
+    // タイトル画面とデモプレイ(ゲームを開始できる画面)ならば真
+    fn in_attract(&self) -> bool {
+        matches!(
+            self.scene,
+            Scene::Title(_)
+                | Scene::HighScores(_)
+                | Scene::History(_)
+                | Scene::Gallery(_)
+                | Scene::Demo(_)
+        )
+    }
+    // クラシックのルールでデモプレイを始める
+    fn start_demo(&mut self) {
+        self.scene = Scene::Demo(DEMO_CNT);
+        self.demo_seed += 1;
+        self.demo_bot = Bot::new(self.demo_seed);
+        self.demo_actions = input::Actions::default();
+        self.game.mode = GameMode::Classic;
+        self.two_players = false;
+        self.active_player = 0;
+        self.game.start(1);
+        self.top.all_clear();
+        self.bottom.all_clear();
+        self.game.reset_stage(None, None);
+    }
+    // デモプレイやポーズメニューからゲームをやめてタイトル画面に戻る(得点は残さない)
+    fn return_to_title(&mut self) {
+        // 音を止める
+        self.game.ufo.reset();
+        self.game.player_bullet.score = 0;
+        for state in self.player_states.iter_mut() {
+            state.score = 0;
+        }
+        self.game.player_exploding = false;
+        self.game.map.all_clear();
+        self.scene = Scene::Title(ATTRACT_CNT);
+    }
+    fn launch_game(&mut self) {
+        self.scene = Scene::LaunchGame(10);
+        // 前回のドットマップをすべて消す
+        self.top.all_clear();
+        self.game.map.all_clear();
+        self.bottom.all_clear();
+    }
+    // ゲームを終了し、ハイスコア表に載るプレイヤーにはイニシャルを入力させる
+    fn finish_game(&mut self) {
+        let scores = self.scores();
+        let player_num = if self.two_players { 2 } else { 1 };
+        // ルールに応じてハイスコア表に載せる値を決める(練習モードは載せない)
+        self.pending_initials = (0..player_num)
+            .filter_map(|i| self.game.mode.result(scores[i]).map(|value| (i, value)))
+            .collect();
+        self.game.player_bullet.score = 0;
+        for state in self.player_states.iter_mut() {
+            state.score = 0;
+        }
+        self.next_initials();
+    }
+    // 次にイニシャルを入力するプレイヤーがいれば入力画面へ、いなければタイトル画面へ進む
+    fn next_initials(&mut self) {
+        while !self.pending_initials.is_empty() {
+            let (player, score) = self.pending_initials.remove(0);
+            let kind = match self.game.mode.kind() {
+                Some(kind) => kind,
+                None => break,
+            };
+            // モダンの得点方式の得点はモダンの表に載せる
+            let scoring = self.game.config.scoring;
+            if self.high_score_table(kind, scoring).qualifies(score) {
+                self.initials = Some(InitialsEntry::new(player, score, kind, scoring));
+                self.scene = Scene::EnterInitials;
+                return;
+            }
+        }
+        self.initials = None;
+        self.scene = Scene::Title(ATTRACT_CNT);
+    }
+    // 画面上部に表示するハイスコア(スコアアタック中はその表の最高得点)
+    fn hi_score(&self) -> i32 {
+        let kind = match self.game.mode.kind() {
+            Some(ModeKind::ScoreAttack) => ModeKind::ScoreAttack,
+            _ => ModeKind::Classic,
+        };
+        self.high_score_table(kind, self.game.config.scoring)
+            .high_score()
+    }
+    // ルールと得点の計算方法で記録するハイスコア表
+    fn high_score_table(&self, kind: ModeKind, scoring: Scoring) -> &HighScoreTable {
+        self.high_scores
+            .iter()
+            .find(|table| table.is_for(kind, scoring))
+            .unwrap()
+    }
+    // 次に表示するハイスコア表へ進める(記録のないモダンの得点の表は飛ばす)
+    fn next_high_scores(&mut self) {
+        loop {
+            self.shown_high_scores = (self.shown_high_scores + 1) % self.high_scores.len();
+            if self.high_scores[self.shown_high_scores].is_shown() {
+                break;
+            }
+        }
+    }
+    // 各プレイヤーの得点
+    fn scores(&self) -> [i32; 2] {
+        let mut scores = [self.player_states[0].score, self.player_states[1].score];
+        scores[self.active_player] = self.game.player_bullet.score;
+        scores
+    }
+    // 画面を消して「PLAY PLAYER<n>」を表示する
+    fn start_player_intro(&mut self) {
+        self.scene = Scene::PlayerIntro(120);
+        self.game.map.all_clear();
+        let text = if self.game.mode.practice().is_some() {
+            "PLAY PRACTICE".to_string()
+        } else if let GameMode::Custom { .. } = self.game.mode {
+            "PLAY TEST".to_string()
+        } else {
+            format!("PLAY PLAYER<{}>", self.active_player + 1)
+        };
+        let pos_x = (canvas::GAME_WIDTH - 8 * text.len() as i32) / 2;
+        array_text(
+            &mut self.game.map.map,
+            Vec2::new(pos_x, 88),
+            &text,
+            &self.char_list,
+        );
+    }
+    // 画面を消してクリアしたステージの成績を表示する
+    fn show_stage_results(&mut self) {
+        self.scene = Scene::LaunchStage(STAGE_RESULTS_CNT);
+        let mut lines = Vec::new();
+        // タイムアタックではスプリットタイムも表示する
+        if let Some(text) = self.game.mode.last_split_text() {
+            lines.push(format!("  {}", text));
+        }
+        if let Some(stats) = self.game.history.last() {
+            lines.extend(stats.lines());
+        }
+        let title = format!("STAGE {} CLEAR", self.game.stage.min(999));
+        self.game.map.all_clear();
+        stats::draw_lines(&mut self.game.map, &self.char_list, &title, &lines);
+    }
+    // 画面を消してゲーム全体の成績を表示し、セッションの記録に残す
+    fn show_game_summary(&mut self) {
+        self.scene = Scene::GameSummary(GAME_SUMMARY_CNT);
+        let total = StageStats::total(&self.game.history);
+        let mode = match self.game.mode.kind() {
+            Some(kind) => kind.name(),
+            None if self.game.mode.practice().is_some() => "PRACTICE",
+            None => "PLAY TEST",
+        };
+        let mut lines = vec![format!("  {:<12}{:>10}", "STAGE", self.game.stage.min(999))];
+        lines.extend(total.lines());
+        let title = if self.two_players {
+            format!("PLAYER<{}> SUMMARY", self.active_player + 1)
+        } else {
+            "GAME SUMMARY".to_string()
+        };
+        self.game.map.all_clear();
+        stats::draw_lines(&mut self.game.map, &self.char_list, &title, &lines);
+        self.session_history.push(GameRecord {
+            mode: mode.to_string(),
+            score: self.game.player_bullet.score,
+            stage: self.game.stage,
+            total,
+        });
+    }
+    // もう一方のプレイヤーに交代する(ゲームオーバーになっていた場合は交代しない)
+    fn change_player(&mut self) {
+        let next = 1 - self.active_player;
+        if self.player_states[next].life <= 0 {
+            return;
+        }
+        self.save_player_state();
+        self.load_player_state(next);
+        self.start_player_intro();
+    }
+    // 操作中のプレイヤーの状態を保存する
+    fn save_player_state(&mut self) {
+        let state = &mut self.player_states[self.active_player];
+        state.score = self.game.player_bullet.score;
+        state.life = self.game.player.life;
+        state.life_up = self.game.player.life_up;
+        state.stage = self.game.stage;
+        state.formation = Some(self.game.alien.save_formation());
+        state.shield = Some(shield::save_rows(&self.game.map));
+        state.stats = std::mem::take(&mut self.game.stats);
+        state.history = std::mem::take(&mut self.game.history);
+        state.combo = std::mem::take(&mut self.game.combo);
+    }
+    // 指定したプレイヤーの状態を読み込んで操作対象にする
+    fn load_player_state(&mut self, i: usize) {
+        self.active_player = i;
+        let state = &mut self.player_states[i];
+        self.game.player.reset_stage();
+        self.game.player.life = state.life;
+        self.game.player.life_up = state.life_up;
+        self.game.player_bullet.reset_stage();
+        self.game.player_bullet.score = state.score;
+        self.game.stage = state.stage;
+        self.game.stats = std::mem::take(&mut state.stats);
+        self.game.history = std::mem::take(&mut state.history);
+        self.game.combo = std::mem::take(&mut state.combo);
+        self.game.ufo.reset();
+        self.game.player_exploding = false;
+    }
 }
 
 #[function_component(App)]
@@ -486,7 +1126,14 @@ fn main() {
     yew::start_app::<App>();
 }
 
-fn draw_title(ctx: &CanvasRenderingContext2d) {
+// show_coin_infoが真の場合はコイン情報も表示する
+// creditsがNoneの場合はフリープレイ
+fn draw_title(
+    ctx: &CanvasRenderingContext2d,
+    show_coin_info: bool,
+    credits: Option<i32>,
+    mode: ModeKind,
+) {
     let ref_pos_x = (canvas::ALL_WIDTH * canvas::SCALE) as f64 / 2.;
     let ref_pos_y = (canvas::ALL_WIDTH * canvas::SCALE) as f64 / 4.;
     ctx.set_font("80px monospace");
@@ -494,27 +1141,87 @@ fn draw_title(ctx: &CanvasRenderingContext2d) {
     ctx.fill_text("Invader", ref_pos_x - 160., ref_pos_y)
         .unwrap();
 
-    ctx.set_font("40px monospace");
-    ctx.fill_text("Press Enter", ref_pos_x - 125., ref_pos_y + 70.)
+    match credits {
+        None => {
+            ctx.set_font("40px monospace");
+            ctx.fill_text("Press Enter", ref_pos_x - 125., ref_pos_y + 70.)
+                .unwrap();
+            ctx.set_font("25px monospace");
+            ctx.fill_text("2 Players: Press 2", ref_pos_x - 135., ref_pos_y + 120.)
+                .unwrap();
+        }
+        Some(0) => {
+            ctx.set_font("40px monospace");
+            ctx.fill_text("INSERT COIN", ref_pos_x - 125., ref_pos_y + 70.)
+                .unwrap();
+            ctx.set_font("25px monospace");
+            ctx.fill_text("Coin: Press C", ref_pos_x - 97., ref_pos_y + 120.)
+                .unwrap();
+        }
+        Some(credits) => {
+            ctx.set_font("40px monospace");
+            ctx.fill_text(
+                &format!("Credit {:02}", credits),
+                ref_pos_x - 110.,
+                ref_pos_y + 70.,
+            )
+            .unwrap();
+            ctx.set_font("25px monospace");
+            let text = if credits < 2 {
+                "1 Player: Press 1"
+            } else {
+                "Press 1 or 2 Players"
+            };
+            ctx.fill_text(text, ref_pos_x - 135., ref_pos_y + 120.)
+                .unwrap();
+        }
+    }
+    ctx.set_font("25px monospace");
+    ctx.fill_text("Options: Press O", ref_pos_x - 120., ref_pos_y + 170.)
         .unwrap();
+    ctx.fill_text("Practice: Press P", ref_pos_x - 127., ref_pos_y + 205.)
+        .unwrap();
+    // 選択中のルール(Mキーで切り替え)
+    let text = format!("Mode: {} (M)", mode.name());
+    ctx.fill_text(&text, ref_pos_x - 7.5 * text.len() as f64, ref_pos_y + 240.)
+        .unwrap();
+    if show_coin_info {
+        ctx.fill_text("*1 Player  1 Coin", ref_pos_x - 127., ref_pos_y + 280.)
+            .unwrap();
+        ctx.fill_text("*2 Players 2 Coins", ref_pos_x - 127., ref_pos_y + 320.)
+            .unwrap();
+    }
 }
 
-fn draw_pause(ctx: &CanvasRenderingContext2d) {
+// デモプレイ中であることと開始方法を表示する
+fn draw_demo(ctx: &CanvasRenderingContext2d) {
     let ref_pos_x = (canvas::ALL_WIDTH * canvas::SCALE) as f64 / 2.;
-    let ref_pos_y = (canvas::ALL_WIDTH * canvas::SCALE) as f64 / 4.;
-    ctx.set_font("80px monospace");
+    let ref_pos_y = ((canvas::TOP_HEIGHT + canvas::GAME_HEIGHT) * canvas::SCALE) as f64 - 120.;
+    ctx.set_font("40px monospace");
     ctx.set_fill_style(&JsValue::from("rgba(200, 10, 10)"));
-    ctx.fill_text("Pause", ref_pos_x - 120., ref_pos_y).unwrap();
+    ctx.fill_text("Demo", ref_pos_x - 45., ref_pos_y).unwrap();
 
-    ctx.set_font("40px monospace");
-    ctx.fill_text("Press Esc", ref_pos_x - 110., ref_pos_y + 60.)
+    ctx.set_font("25px monospace");
+    ctx.fill_text("Press Start", ref_pos_x - 82., ref_pos_y + 35.)
         .unwrap();
 }
-fn draw_gameover_message(ctx: &CanvasRenderingContext2d) {
+// 2人プレイ時はゲームオーバーになったプレイヤーも表示する
+// タイムアタックで目標を達成した場合はクリアと表示する
+fn draw_gameover_message(ctx: &CanvasRenderingContext2d, player_num: Option<usize>, cleared: bool) {
     let ref_pos_x = (canvas::ALL_WIDTH * canvas::SCALE) as f64 / 2.;
     let ref_pos_y = (canvas::ALL_WIDTH * canvas::SCALE) as f64 / 4.;
     ctx.set_font("70px monospace");
     ctx.set_fill_style(&JsValue::from("rgba(200, 10, 10)"));
-    ctx.fill_text("Game over", ref_pos_x - 170., ref_pos_y)
-        .unwrap();
+    if cleared {
+        ctx.fill_text("Clear!", ref_pos_x - 105., ref_pos_y)
+            .unwrap();
+    } else {
+        ctx.fill_text("Game over", ref_pos_x - 170., ref_pos_y)
+            .unwrap();
+    }
+    if let Some(num) = player_num {
+        ctx.set_font("40px monospace");
+        ctx.fill_text(&format!("Player {}", num), ref_pos_x - 95., ref_pos_y + 60.)
+            .unwrap();
+    }
 }
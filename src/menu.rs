@@ -0,0 +1,70 @@
+use crate::array_sprite::array_text;
+use crate::canvas;
+use crate::dot_map::DotMap;
+use crate::input::Actions;
+use crate::key_binding::Control;
+use crate::math::Vec2;
+
+// メニューに対する操作
+#[derive(PartialEq, Clone, Copy)]
+pub enum MenuInput {
+    Left,   // 選択中の項目の値を減らす
+    Right,  // 選択中の項目の値を増やす
+    Select, // 選択中の項目を決定する(ショットボタン)
+    Back,   // メニューを閉じる(Escキー)
+    None,
+}
+
+// 上下キーで項目を選ぶメニュー
+pub struct Menu {
+    // 選択中の項目のインデックス番号
+    pub cursor: usize,
+    item_num: usize,
+}
+
+impl Menu {
+    pub fn new(item_num: usize) -> Self {
+        Menu {
+            cursor: 0,
+            item_num,
+        }
+    }
+    // キーが押された瞬間のみ反応し(前の画面から押しっぱなしのキーには反応しない)、上下キーはカーソルを移動させる
+    pub fn update(&mut self, actions: &Actions) -> MenuInput {
+        if actions.just_pressed(Control::Up) {
+            self.cursor = (self.cursor + self.item_num - 1) % self.item_num;
+        } else if actions.just_pressed(Control::Down) {
+            self.cursor = (self.cursor + 1) % self.item_num;
+        }
+        if actions.just_pressed(Control::Left) {
+            MenuInput::Left
+        } else if actions.just_pressed(Control::Right) {
+            MenuInput::Right
+        } else if actions.just_pressed(Control::Shot) {
+            MenuInput::Select
+        } else if actions.just_pressed(Control::Pause) {
+            MenuInput::Back
+        } else {
+            MenuInput::None
+        }
+    }
+    // 見出しと項目を描画し、選択中の項目の左にカーソルを置く
+    pub fn draw(
+        &self,
+        dot_map: &mut DotMap,
+        char_sprite: &[Vec<u8>],
+        title: &str,
+        items: &[String],
+    ) {
+        let title_x = (dot_map.map[0].len() as i32 - 8 * title.len() as i32) / 2;
+        array_text(&mut dot_map.map, Vec2::new(title_x, 16), title, char_sprite);
+        // 項目が多い場合は画面に収まるように上に詰める
+        let top = (canvas::GAME_HEIGHT - 16 * items.len() as i32).clamp(32, 48);
+        for (i, item) in items.iter().enumerate() {
+            let pos_y = top + 16 * i as i32;
+            let cursor = if i == self.cursor { ">" } else { " " };
+            array_text(&mut dot_map.map, Vec2::new(16, pos_y), cursor, char_sprite);
+            array_text(&mut dot_map.map, Vec2::new(32, pos_y), item, char_sprite);
+        }
+    }
+}
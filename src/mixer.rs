@@ -0,0 +1,161 @@
+use web_sys::{AudioContext, GainNode};
+
+// 音量の段階(10で100%、1段階で10%)
+const DEFAULT_LEVEL: i32 = 10;
+const MAX_LEVEL: i32 = 20;
+
+// 種類ごとに音をまとめて音量を調整するバス
+// March、Effects、UfoのバスはMasterのバスを通して出力する
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bus {
+    Master,
+    March,   // インベーダーの移動音
+    Effects, // 発射音と爆発音
+    Ufo,     // UFOの飛行音と爆発音
+}
+
+impl Bus {
+    pub const ALL: [Bus; 4] = [Bus::Master, Bus::March, Bus::Effects, Bus::Ufo];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Bus::Master => "MASTER",
+            Bus::March => "MARCH",
+            Bus::Effects => "EFFECTS",
+            Bus::Ufo => "UFO",
+        }
+    }
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|b| *b == self).unwrap()
+    }
+}
+
+#[derive(Clone)]
+struct Channel {
+    level: i32,
+    muted: bool,
+    // 音を鳴らさない場合はNone
+    gain: Option<GainNode>,
+}
+
+// バスごとのGainNodeをつないだミキサー
+// GainNodeの音量を変えるので、ループ再生中の音にもすぐに反映される
+#[derive(Clone)]
+pub struct Mixer {
+    channels: [Channel; Bus::ALL.len()],
+}
+
+impl Mixer {
+    // contextがNoneならばGainNodeを作らず、音量の値だけを持つ
+    pub fn new(context: Option<&AudioContext>) -> Self {
+        let master = context.map(|context| {
+            let gain = context.create_gain().unwrap();
+            gain.connect_with_audio_node(&context.destination())
+                .unwrap();
+            gain
+        });
+        let channels = Bus::ALL.map(|bus| {
+            let gain = match bus {
+                Bus::Master => master.clone(),
+                _ => context.zip(master.as_ref()).map(|(context, master)| {
+                    let gain = context.create_gain().unwrap();
+                    gain.connect_with_audio_node(master).unwrap();
+                    gain
+                }),
+            };
+            Channel {
+                level: DEFAULT_LEVEL,
+                muted: false,
+                gain,
+            }
+        });
+        Mixer { channels }
+    }
+    // バスに音を流し込むノード
+    pub fn input(&self, bus: Bus) -> Option<&GainNode> {
+        self.channels[bus.index()].gain.as_ref()
+    }
+    pub fn level(&self, bus: Bus) -> i32 {
+        self.channels[bus.index()].level
+    }
+    pub fn is_muted(&self, bus: Bus) -> bool {
+        self.channels[bus.index()].muted
+    }
+    // 表示用の音量(%)
+    pub fn percent(&self, bus: Bus) -> i32 {
+        self.level(bus) * 100 / DEFAULT_LEVEL
+    }
+    // 音量を段階数だけ変える(0～MAX_LEVELに収める)
+    pub fn change_level(&mut self, bus: Bus, diff: i32) {
+        let channel = &mut self.channels[bus.index()];
+        channel.level = (channel.level + diff).clamp(0, MAX_LEVEL);
+        self.apply(bus);
+    }
+    pub fn toggle_mute(&mut self, bus: Bus) {
+        let channel = &mut self.channels[bus.index()];
+        channel.muted = !channel.muted;
+        self.apply(bus);
+    }
+    // すべてのバスの音量を初期値に戻し、ミュートを解除する
+    pub fn reset(&mut self) {
+        for bus in Bus::ALL {
+            let channel = &mut self.channels[bus.index()];
+            channel.level = DEFAULT_LEVEL;
+            channel.muted = false;
+            self.apply(bus);
+        }
+    }
+    // 別のミキサーの音量とミュートを引き継ぐ(GainNodeは自身のものを使う)
+    pub fn copy_levels_from(&mut self, other: &Mixer) {
+        for bus in Bus::ALL {
+            let channel = &mut self.channels[bus.index()];
+            channel.level = other.level(bus);
+            channel.muted = other.is_muted(bus);
+            self.apply(bus);
+        }
+    }
+    // 音量をGainNodeに設定する
+    fn apply(&self, bus: Bus) {
+        let channel = &self.channels[bus.index()];
+        if let Some(gain) = &channel.gain {
+            let value = if channel.muted {
+                0.
+            } else {
+                channel.level as f32 / DEFAULT_LEVEL as f32
+            };
+            gain.gain().set_value(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_level_is_clamped() {
+        let mut mixer = Mixer::new(None);
+        mixer.change_level(Bus::Ufo, -100);
+        assert_eq!(mixer.level(Bus::Ufo), 0);
+        mixer.change_level(Bus::Ufo, 100);
+        assert_eq!(mixer.percent(Bus::Ufo), 200);
+        assert_eq!(mixer.level(Bus::Master), DEFAULT_LEVEL);
+    }
+
+    #[test]
+    fn copy_levels_from_keeps_levels_and_mutes() {
+        // 音声の読み込み前に変えた音量を、読み込み後のミキサーに引き継ぐ
+        let mut old = Mixer::new(None);
+        old.change_level(Bus::Master, -3);
+        old.toggle_mute(Bus::March);
+        let mut new = Mixer::new(None);
+        new.copy_levels_from(&old);
+        for bus in Bus::ALL {
+            assert_eq!(new.level(bus), old.level(bus));
+            assert_eq!(new.is_muted(bus), old.is_muted(bus));
+        }
+        new.reset();
+        assert_eq!(new.level(Bus::Master), DEFAULT_LEVEL);
+        assert!(!new.is_muted(Bus::March));
+    }
+}
@@ -1,27 +1,328 @@
+use crate::array_sprite::array_text;
+use crate::audio::Audio;
+use crate::dot_map::DotMap;
+use crate::input::{Actions, Keyboard};
+use crate::key_binding::{key_label, Control, KeyBindings};
+use crate::math::Vec2;
+use crate::menu::{Menu, MenuInput};
+use crate::mixer::Bus;
+
+// ポーズメニューの最初のページの項目
+const TOP_ITEMS: [&str; 5] = [
+    "RESUME",
+    "RESTART STAGE",
+    "QUIT TO TITLE",
+    "VOLUME",
+    "CONTROLS",
+];
+// 音量のページの項目数(バスごとの音量、初期値に戻す、戻る)
+const VOLUME_ITEM_NUM: usize = Bus::ALL.len() + 2;
+// キー割り当てで、キーの入力を待つtick数(5秒)
+const CAPTURE_CNT: i32 = 300;
+// キー割り当てのページの項目数(割り当てを変更できる操作、初期設定に戻す、戻る)
+const CONTROLS_ITEM_NUM: usize = Control::REBINDABLE.len() + 2;
+
+// ポーズメニューで選ばれた操作
+#[derive(Debug, PartialEq)]
+pub enum PauseAction {
+    None,
+    // ゲームを再開する
+    Resume,
+    // 現在のステージを最初からやり直す
+    RestartStage,
+    // ゲームをやめてタイトル画面に戻る
+    Quit,
+    // キー割り当てを変更した(保存する)
+    BindingsChanged,
+}
+
+// ポーズメニューのページ
+#[derive(PartialEq)]
+enum Page {
+    Top,
+    Volume,
+    Controls,
+}
+
 pub struct Pause {
-    pre_pause_button: bool,
+    menu: Menu,
+    page: Page,
+    // キーの入力を待っている操作
+    capturing: Option<Control>,
+    // キーの入力を待つ残りtick数
+    capture_cnt: i32,
+    // キー割り当てのページに表示するメッセージ
+    message: Option<String>,
+    // ポーズメニューを描くドットマップ(ゲーム画面のドットマップは書き換えない)
+    pub map: DotMap,
+}
+
+impl Default for Pause {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Pause {
     pub fn new() -> Self {
         Pause {
-            // pause: false,
-            pre_pause_button: false,
+            menu: Menu::new(TOP_ITEMS.len()),
+            page: Page::Top,
+            capturing: None,
+            capture_cnt: 0,
+            message: None,
+            map: DotMap::new(),
+        }
+    }
+    // ポーズメニューを最初のページから開く
+    pub fn open(&mut self) {
+        self.open_page(Page::Top);
+    }
+    // 上下キーで項目を選び、ショットボタンで決定する(Escキーで前のページに戻る)
+    // キー割り当てのページで操作を選ぶと、次に押されたキーをその操作に割り当てる
+    pub fn update(
+        &mut self,
+        actions: &Actions,
+        keyboard: &mut Keyboard,
+        audio: &mut Audio,
+    ) -> PauseAction {
+        if let Some(control) = self.capturing {
+            return self.capture_key(control, actions, keyboard);
+        }
+        let cursor = self.menu.cursor;
+        let input = self.menu.update(actions);
+        match self.page {
+            Page::Top => match input {
+                MenuInput::Select => match cursor {
+                    0 => return PauseAction::Resume,
+                    1 => return PauseAction::RestartStage,
+                    2 => return PauseAction::Quit,
+                    3 => self.open_page(Page::Volume),
+                    _ => self.open_page(Page::Controls),
+                },
+                MenuInput::Back => return PauseAction::Resume,
+                _ => (),
+            },
+            // 左右キーでバスの音量を変え、ショットボタンでミュートを切り替える
+            Page::Volume => match input {
+                MenuInput::Left if cursor < Bus::ALL.len() => {
+                    audio.mixer.change_level(Bus::ALL[cursor], -1)
+                }
+                MenuInput::Right if cursor < Bus::ALL.len() => {
+                    audio.mixer.change_level(Bus::ALL[cursor], 1)
+                }
+                MenuInput::Select if cursor < Bus::ALL.len() => {
+                    audio.mixer.toggle_mute(Bus::ALL[cursor])
+                }
+                MenuInput::Select if cursor == Bus::ALL.len() => audio.mixer.reset(),
+                MenuInput::Select | MenuInput::Back => self.open_page(Page::Top),
+                _ => (),
+            },
+            Page::Controls => match input {
+                MenuInput::Select if cursor < Control::REBINDABLE.len() => {
+                    // 項目を選んだキーを割り当てないように、これから押されるキーを待つ
+                    keyboard.last_key = None;
+                    self.capturing = Some(Control::REBINDABLE[cursor]);
+                    self.capture_cnt = CAPTURE_CNT;
+                    self.message = Some("PAUSE TO CANCEL".to_string());
+                }
+                MenuInput::Select if cursor == Control::REBINDABLE.len() => {
+                    keyboard.bindings = KeyBindings::default();
+                    self.message = Some("RESET TO DEFAULTS".to_string());
+                    return PauseAction::BindingsChanged;
+                }
+                MenuInput::Select | MenuInput::Back => self.open_page(Page::Top),
+                _ => (),
+            },
+        }
+        PauseAction::None
+    }
+    // ポーズメニューを自身のドットマップに描く
+    pub fn draw(&mut self, char_list: &[Vec<u8>], audio: &Audio, bindings: &KeyBindings) {
+        let (title, items): (&str, Vec<String>) = match self.page {
+            Page::Top => ("PAUSE", TOP_ITEMS.iter().map(|s| s.to_string()).collect()),
+            Page::Volume => ("VOLUME", Self::volume_items(audio)),
+            Page::Controls => ("CONTROLS", self.controls_items(bindings)),
+        };
+        self.map.all_clear();
+        self.menu.draw(&mut self.map, char_list, title, &items);
+        if let Some(message) = &self.message {
+            array_text(&mut self.map.map, Vec2::new(0, 200), message, char_list);
         }
     }
-    // ポーズ停止または解除する瞬間のみ真を返す
-    pub fn toggle_pause(&mut self, pause_button: bool) -> bool {
-        // ポーズボタンが押された
-        if !self.pre_pause_button && pause_button {
-            // ポーズ開始/解除
-            // self.pause = !self.pause;
-            self.pre_pause_button = true;
-            return true;
+    // 音量のページの項目(バス名、音量、ミュート中ならばMUTE)
+    fn volume_items(audio: &Audio) -> Vec<String> {
+        let mut items: Vec<String> = Bus::ALL
+            .iter()
+            .map(|bus| {
+                let mute = if audio.mixer.is_muted(*bus) {
+                    "MUTE"
+                } else {
+                    ""
+                };
+                format!(
+                    "{:<8}< {:>3}% > {}",
+                    bus.name(),
+                    audio.mixer.percent(*bus),
+                    mute
+                )
+            })
+            .collect();
+        items.push("RESET".to_string());
+        items.push("BACK".to_string());
+        items
+    }
+    // キー割り当てのページの項目(操作名と割り当てたキー)
+    fn controls_items(&self, bindings: &KeyBindings) -> Vec<String> {
+        let mut items: Vec<String> = Control::REBINDABLE
+            .iter()
+            .map(|control| {
+                let keys = if self.capturing == Some(*control) {
+                    "PRESS A KEY".to_string()
+                } else {
+                    let labels: Vec<String> = bindings
+                        .keys(*control)
+                        .iter()
+                        .map(|k| key_label(k))
+                        .collect();
+                    labels.join(" ")
+                };
+                // カーソルの右から画面の右端までの24文字に収める
+                format!("{:<9}{}", control.name(), keys)
+                    .chars()
+                    .take(24)
+                    .collect()
+            })
+            .collect();
+        items.push("RESET".to_string());
+        items.push("BACK".to_string());
+        items
+    }
+    // 押されたキーを操作に割り当てる
+    // ポーズの操作(ゲームパッドやタッチ操作のボタンも含む)を行うか、一定時間キーが押されなければ取り消す
+    fn capture_key(
+        &mut self,
+        control: Control,
+        actions: &Actions,
+        keyboard: &mut Keyboard,
+    ) -> PauseAction {
+        self.capture_cnt -= 1;
+        if actions.just_pressed(Control::Pause) || self.capture_cnt < 0 {
+            keyboard.last_key = None;
+            self.capturing = None;
+            self.message = Some("CANCELLED".to_string());
+            return PauseAction::None;
         }
-        if self.pre_pause_button && !pause_button {
-            // ポーズボタンが押されなくなった
-            self.pre_pause_button = false;
+        let key = match keyboard.last_key.take() {
+            Some(key) => key,
+            None => return PauseAction::None,
+        };
+        self.capturing = None;
+        // 他の操作に割り当て済みのキーはその操作から移す(最後のキーならば交換する)
+        self.message = keyboard
+            .bindings
+            .bind(control, &key)
+            .map(|moved| match moved.swapped {
+                Some(swapped) => format!("{} NOW ON {}", key_label(&swapped), moved.from.name()),
+                None => format!("{} MOVED FROM {}", key_label(&key), moved.from.name()),
+            });
+        PauseAction::BindingsChanged
+    }
+    fn open_page(&mut self, page: Page) {
+        let item_num = match page {
+            Page::Top => TOP_ITEMS.len(),
+            Page::Volume => VOLUME_ITEM_NUM,
+            Page::Controls => CONTROLS_ITEM_NUM,
+        };
+        self.page = page;
+        self.capturing = None;
+        self.message = None;
+        self.menu = Menu::new(item_num);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // キー割り当てのページで最初の操作を選び、キーの入力を待つ状態にする
+    fn start_capture(keyboard: &mut Keyboard, audio: &mut Audio) -> (Pause, Actions) {
+        let mut pause = Pause::new();
+        let mut actions = Actions::default();
+        pause.open_page(Page::Controls);
+        actions.sample_controls(&[Control::Shot]);
+        pause.update(&actions, keyboard, audio);
+        assert_eq!(pause.capturing, Some(Control::REBINDABLE[0]));
+        actions.sample_controls(&[]);
+        (pause, actions)
+    }
+
+    #[test]
+    fn captured_key_moves_from_other_control() {
+        let mut keyboard = Keyboard::default();
+        let mut audio = Audio::silent();
+        let (mut pause, actions) = start_capture(&mut keyboard, &mut audio);
+        assert_eq!(
+            pause.update(&actions, &mut keyboard, &mut audio),
+            PauseAction::None
+        );
+        // 発射に割り当て済みのzを移す
+        keyboard.last_key = Some("z".to_string());
+        assert_eq!(
+            pause.update(&actions, &mut keyboard, &mut audio),
+            PauseAction::BindingsChanged
+        );
+        assert_eq!(pause.capturing, None);
+        let control = Control::REBINDABLE[0];
+        assert_eq!(keyboard.bindings.control_of("z"), Some(control));
+        assert!(pause.message.unwrap().contains(Control::Shot.name()));
+    }
+
+    #[test]
+    fn captured_key_swaps_last_key_of_other_control() {
+        let mut keyboard = Keyboard::default();
+        let mut audio = Audio::silent();
+        let (mut pause, actions) = start_capture(&mut keyboard, &mut audio);
+        let control = Control::REBINDABLE[0];
+        let swapped = keyboard.bindings.keys(control)[0].clone();
+        // 設定画面のキーは1つだけなので、代わりのキーを渡す
+        keyboard.last_key = Some("o".to_string());
+        pause.update(&actions, &mut keyboard, &mut audio);
+        assert_eq!(keyboard.bindings.control_of("o"), Some(control));
+        assert_eq!(keyboard.bindings.keys(Control::Options), [swapped.as_str()]);
+        assert_eq!(
+            pause.message.unwrap(),
+            format!("{} NOW ON {}", key_label(&swapped), Control::Options.name())
+        );
+    }
+
+    #[test]
+    fn pause_action_cancels_capture() {
+        let mut keyboard = Keyboard::default();
+        let mut audio = Audio::silent();
+        let (mut pause, mut actions) = start_capture(&mut keyboard, &mut audio);
+        // ポーズのキーを押した時は、そのキーを割り当てずに取り消す
+        actions.sample_controls(&[Control::Pause]);
+        keyboard.last_key = Some("Escape".to_string());
+        assert_eq!(
+            pause.update(&actions, &mut keyboard, &mut audio),
+            PauseAction::None
+        );
+        assert_eq!(pause.capturing, None);
+        assert_eq!(keyboard.last_key, None);
+        assert_eq!(keyboard.bindings.control_of("Escape"), Some(Control::Pause));
+    }
+
+    #[test]
+    fn capture_times_out() {
+        let mut keyboard = Keyboard::default();
+        let mut audio = Audio::silent();
+        let (mut pause, actions) = start_capture(&mut keyboard, &mut audio);
+        for _ in 0..CAPTURE_CNT {
+            pause.update(&actions, &mut keyboard, &mut audio);
         }
-        false
+        assert!(pause.capturing.is_some());
+        pause.update(&actions, &mut keyboard, &mut audio);
+        assert_eq!(pause.capturing, None);
     }
 }
@@ -1,22 +1,44 @@
 use crate::alien::Alien;
 use crate::audio::Audio;
 use crate::canvas;
-use crate::input::KeyDown;
+use crate::config::GameConfig;
+use crate::input::Actions;
+use crate::key_binding::Control;
 use crate::math::Vec2;
 use crate::ufo::Ufo;
 use crate::{array_sprite::ArraySprite, dot_map::DotMap};
 // プレイヤーの弾のスピード
-const PLAYER_BULLET_DELTA: i32 = 4;
+pub const PLAYER_BULLET_DELTA: i32 = 4;
+
+// プレイヤーの弾が何に当たったか
+#[derive(Clone, Copy, PartialEq)]
+pub enum ShotResult {
+    // エイリアンのインデックス番号、種類(0: 下段のタコ, 1: 中段のカニ, 2: 上段のイカ)と獲得点数
+    Alien {
+        index: usize,
+        alien_type: usize,
+        score: i32,
+    },
+    Ufo {
+        score: i32,
+    },
+    // シールドやエイリアンの弾など
+    Obstacle,
+    // 画面上部に届いた
+    Top,
+}
 
 pub struct Bullet {
-    pos: Vec2,                   // 左上位置
-    live: bool,                  // 弾が存在しているか否か
-    explosion_effect_show: bool, // 爆発エフェクトを表示するならば真
-    ban_fire_cnt: Option<i32>,   // 射撃禁止状態の残りカウント
-    pub fire_cnt: i32,           // ステージ開始からの累計射撃数
-    pub score: i32,              // 獲得点数
-    sprite: Vec<u8>,             // 左側から縦8ピクセルずつを8bitのベクタで表す
-    explosion_sprite: Vec<u8>,   // 爆発画像
+    pos: Vec2,                           // 左上位置
+    live: bool,                          // 弾が存在しているか否か
+    explosion_effect_show: bool,         // 爆発エフェクトを表示するならば真
+    ban_fire_cnt: Option<i32>,           // 射撃禁止状態の残りカウント
+    hit_pos: Option<Vec2>,               // このフレームで障害物に当たった位置
+    pub fire_cnt: i32,                   // ステージ開始からの累計射撃数
+    pub score: i32,                      // 獲得点数
+    pub shot_result: Option<ShotResult>, // このフレームで弾が当たったもの(Game::updateで毎フレーム消す)
+    sprite: Vec<u8>,                     // 左側から縦8ピクセルずつを8bitのベクタで表す
+    explosion_sprite: Vec<u8>,           // 爆発画像
 }
 
 impl Bullet {
@@ -26,10 +48,12 @@ impl Bullet {
             live: false,
             explosion_effect_show: false,
             ban_fire_cnt: None,
+            hit_pos: None,
             fire_cnt: 0,
             sprite,
             explosion_sprite,
             score: 0,
+            shot_result: None,
         }
     }
     // 弾を発射
@@ -47,11 +71,16 @@ impl Bullet {
         self.reset_stage();
         self.score = 0;
     }
+    // 弾が画面上に無く、射撃禁止状態でなければ真
+    pub fn can_fire(&self) -> bool {
+        !self.live && self.ban_fire_cnt.is_none()
+    }
     pub fn reset_stage(&mut self) {
         self.live = false;
         self.explosion_effect_show = false;
         // 開始後一定時間は射撃禁止
         self.ban_fire_cnt = Some(80);
+        self.hit_pos = None;
         self.fire_cnt = 0;
     }
     pub fn update(
@@ -60,9 +89,10 @@ impl Bullet {
         player: &mut Player,
         ufo: &mut Ufo,
         alien: &mut Alien,
-        input_key: &KeyDown,
+        fire: bool,
         audio: &Audio,
     ) {
+        self.hit_pos = None;
         // 弾が存在していたら
         if self.live {
             // 前回の弾を消す
@@ -76,6 +106,7 @@ impl Bullet {
                 self.live = false;
                 self.ban_fire_cnt = Some(15);
                 self.explosion_effect_show = true;
+                self.shot_result = Some(ShotResult::Top);
                 // 自身のx座標が爆発エフェクトの中心になるようずらす
                 self.pos.x = self.pos.x - self.explosion_sprite.len() as i32 / 2;
             } else {
@@ -85,8 +116,8 @@ impl Bullet {
                 }
             }
         } else {
-            // 弾が画面上に無く、射撃可能状態で、プレイヤーが生きていて、かつ発射ボタンが押された場合(スペース、Enter)
-            if self.ban_fire_cnt == None && player.explosion_cnt == None && input_key.shot {
+            // 弾が画面上に無く、射撃可能状態で、プレイヤーが生きていて、かつ発射ボタンが押された場合
+            if self.ban_fire_cnt == None && player.explosion_cnt == None && fire {
                 self.fire(player.pos.x + 7, player.pos.y - 8);
                 // 発射音再生
                 if let Some(sound) = &audio.player_shot {
@@ -98,12 +129,6 @@ impl Bullet {
                 }
             }
         }
-        // スコアボーナス
-        if 1500 <= self.score && !player.life_up {
-            player.life += 1;
-            // 残機が増えるのは1度だけ
-            player.life_up = true;
-        }
         self.draw(dot_map);
     }
 
@@ -113,12 +138,15 @@ impl Bullet {
         self.ban_fire_cnt = Some(15);
         // 爆発エフェクトを表示する
         self.explosion_effect_show = true;
+        self.shot_result = Some(ShotResult::Obstacle);
         // 衝突したのがUFOだった場合
         if self.pos.y / 8 < 2 {
             // UFOの爆発エフェクト表示中でなければ
             if ufo.explosion.show_cnt == None {
                 // UFOの撃破時には点数を加算
-                self.score += ufo.hit_player_bullet(dot_map, self.fire_cnt, audio);
+                let score = ufo.hit_player_bullet(dot_map, self.fire_cnt, audio);
+                self.score += score;
+                self.shot_result = Some(ShotResult::Ufo { score });
             }
             // 爆発エフェクトは表示しない
             self.explosion_effect_show = false;
@@ -127,16 +155,55 @@ impl Bullet {
             // エイリアンに当たっていた場合
             if let Some(i) = alien.pos2index(self.pos) {
                 // 撃破したエイリアンの点数を追加
-                self.score += Alien::index2score(i);
+                let score = alien.index2score(i);
+                self.score += score;
+                self.shot_result = Some(ShotResult::Alien {
+                    index: i,
+                    alien_type: alien.index2type(i),
+                    score,
+                });
                 alien.remove(dot_map, i, audio);
                 // 爆発エフェクトは表示しない
                 self.explosion_effect_show = false;
             }
         }
+        // UFOとエイリアン以外(シールドやエイリアンの弾)に当たった場合は位置を残す
+        if self.explosion_effect_show {
+            self.hit_pos = Some(self.pos);
+        }
         // 自身のx座標が爆発エフェクトの中心になるようずらす
         self.pos.x = self.pos.x - 4;
         self.pos.y += 2;
     }
+    // 弾同士の当たり判定に使う、弾の見えている部分(1x4ドット)の上端の座標
+    // 飛翔中のみSomeを返す
+    pub fn shot_pos(&self) -> Option<Vec2> {
+        if !self.live {
+            return None;
+        }
+        Some(Vec2::new(self.pos.x, self.pos.y + 4))
+    }
+    // このフレームで障害物(シールドやエイリアンの弾)に当たった場合の、当たる直前の弾の見えている部分の上端の座標
+    // プレイヤーの弾の更新で毎フレーム消すので、その直後の判定にのみ使う
+    pub fn hit_shot_pos(&self) -> Option<Vec2> {
+        let pos = self.hit_pos?;
+        Some(Vec2::new(pos.x, pos.y + 4))
+    }
+    // エイリアンの弾と衝突した時に、その場で爆発させる
+    pub fn explode(&mut self, dot_map: &mut DotMap) {
+        if !self.live {
+            return;
+        }
+        // 描画済みの弾を消す
+        self.erase_shifted(dot_map, self.pos);
+        self.live = false;
+        self.ban_fire_cnt = Some(15);
+        self.explosion_effect_show = true;
+        self.shot_result = Some(ShotResult::Obstacle);
+        // 自身のx座標が爆発エフェクトの中心になるようずらす
+        self.pos.x -= 4;
+        self.pos.y += 2;
+    }
 
     // プレイヤーの弾をドットマップに描画(縦方向のバイト境界をまたぐ可能性有り)
     fn draw(&mut self, dot_map: &mut DotMap) {
@@ -178,14 +245,14 @@ impl ArraySprite for Bullet {
 }
 
 pub struct Player {
-    width: i32,                       // 描画サイズの幅
+    pub width: i32,                   // 描画サイズの幅
     pub pos: Vec2,                    // 左上位置
     pre_pos: Vec2,                    // 前回描画時の位置
     pub const_max_explosion_cnt: i32, // 撃破されてから再出撃までのカウント数(定数)
     pub explosion_cnt: Option<i32>,   // Some(再出撃までの残りカウント)
     pub life: i32,
-    life_up: bool,   // スコアボーナスで残機が増加済であれば真
-    sprite: Vec<u8>, // 左側から縦8ピクセルずつを8bitのベクタで表す
+    pub life_up: bool, // スコアボーナスで残機が増加済であれば真
+    sprite: Vec<u8>,   // 左側から縦8ピクセルずつを8bitのベクタで表す
     explosion_sprite: [Vec<u8>; 2],
 }
 impl Player {
@@ -196,15 +263,16 @@ impl Player {
             pre_pos: Vec2::new(8, canvas::GAME_HEIGHT - 8 * 3),
             const_max_explosion_cnt: 160,
             explosion_cnt: None,
-            life: 3,
+            // ゲーム開始時にreset_allで設定する
+            life: 0,
             life_up: false,
             sprite,
             explosion_sprite: [explosion_sprite1, explosion_sprite2],
         }
     }
-    pub fn reset_all(&mut self) {
+    pub fn reset_all(&mut self, config: &GameConfig) {
         self.reset_stage();
-        self.life = 3;
+        self.life = config.start_life;
         self.life_up = false;
     }
     pub fn reset_stage(&mut self) {
@@ -212,7 +280,15 @@ impl Player {
         self.pre_pos = Vec2::new(8, canvas::GAME_HEIGHT - 8 * 3);
         self.explosion_cnt = None;
     }
-    pub fn update(&mut self, dot_map: &mut DotMap, input_key: &KeyDown) {
+    // スコアボーナス
+    pub fn check_extra_life(&mut self, score: i32, config: &GameConfig) {
+        if config.extra_life_score <= score && !self.life_up {
+            self.life += 1;
+            // 残機が増えるのは1度だけ
+            self.life_up = true;
+        }
+    }
+    pub fn update(&mut self, dot_map: &mut DotMap, actions: &Actions) {
         self.pre_pos = self.pos;
         // 撃破後、復活前
         if let Some(cnt) = self.explosion_cnt {
@@ -237,11 +313,11 @@ impl Player {
         }
 
         // プレイヤー移動範囲制限
-        if 7 < self.pos.x && input_key.left {
+        if 7 < self.pos.x && actions.held(Control::Left) {
             // 左に移動
             self.pos.x -= 1;
         }
-        if self.pos.x + self.width < canvas::GAME_WIDTH - 7 && input_key.right {
+        if self.pos.x + self.width < canvas::GAME_WIDTH - 7 && actions.held(Control::Right) {
             // 右に移動
             self.pos.x += 1;
         }
@@ -0,0 +1,36 @@
+use crate::alien::Formation;
+use crate::combo::Combo;
+use crate::stats::StageStats;
+
+// 2人プレイ時、交代で待機しているプレイヤーの状態を保存する
+pub struct PlayerState {
+    pub score: i32,
+    pub life: i32,
+    pub life_up: bool, // スコアボーナスで残機が増加済であれば真
+    pub stage: usize,
+    // 生き残っているエイリアンの隊列(ステージ開始前はNone)
+    pub formation: Option<Formation>,
+    // 削られたシールドを含む行のドットマップ(ステージ開始前はNone)
+    pub shield: Option<Vec<Vec<u8>>>,
+    // 遊んでいるステージと終えたステージの成績
+    pub stats: StageStats,
+    pub history: Vec<StageStats>,
+    // モダンの得点方式での連続命中の状態
+    pub combo: Combo,
+}
+
+impl PlayerState {
+    pub fn new(score: i32, life: i32, life_up: bool, stage: usize) -> Self {
+        PlayerState {
+            score,
+            life,
+            life_up,
+            stage,
+            formation: None,
+            shield: None,
+            stats: StageStats::default(),
+            history: Vec::new(),
+            combo: Combo::default(),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::config::on_off;
+
+// 選択できる最大の開始ステージ
+const MAX_STAGE: usize = 99;
+
+// 練習モードの設定
+#[derive(Clone)]
+pub struct Practice {
+    // 開始するステージ
+    pub stage: usize,
+    // 真の場合は撃破されても残機が減らない
+    pub infinite_life: bool,
+    // 偽の場合はUFOが出現しない
+    pub ufo: bool,
+    // 各種類のエイリアンの弾を撃たせるならば真(rolling, plunger, squigglyの順)
+    pub shot_types: [bool; 3],
+}
+
+// 練習モード設定画面の項目
+#[derive(Clone, Copy)]
+enum PracticeItem {
+    Stage,
+    InfiniteLife,
+    Ufo,
+    RollingShot,
+    PlungerShot,
+    SquigglyShot,
+}
+const ITEMS: [PracticeItem; 6] = [
+    PracticeItem::Stage,
+    PracticeItem::InfiniteLife,
+    PracticeItem::Ufo,
+    PracticeItem::RollingShot,
+    PracticeItem::PlungerShot,
+    PracticeItem::SquigglyShot,
+];
+
+impl Default for Practice {
+    fn default() -> Self {
+        Practice {
+            stage: 1,
+            infinite_life: true,
+            ufo: true,
+            shot_types: [true; 3],
+        }
+    }
+}
+
+impl Practice {
+    // 設定画面の項目数
+    pub const ITEM_NUM: usize = ITEMS.len();
+
+    // 設定画面に表示する項目名と値
+    pub fn item_text(&self, i: usize) -> String {
+        match ITEMS[i] {
+            PracticeItem::Stage => format!("STAGE          {}", self.stage),
+            PracticeItem::InfiniteLife => {
+                format!("INFINITE LIVES {}", on_off(self.infinite_life))
+            }
+            PracticeItem::Ufo => format!("UFO            {}", on_off(self.ufo)),
+            PracticeItem::RollingShot => {
+                format!("ROLLING SHOT   {}", on_off(self.shot_types[0]))
+            }
+            PracticeItem::PlungerShot => {
+                format!("PLUNGER SHOT   {}", on_off(self.shot_types[1]))
+            }
+            PracticeItem::SquigglyShot => {
+                format!("SQUIGGLY SHOT  {}", on_off(self.shot_types[2]))
+            }
+        }
+    }
+    // 設定画面で項目の値を切り替える(forwardが真なら次の値、偽なら前の値)
+    pub fn change_item(&mut self, i: usize, forward: bool) {
+        match ITEMS[i] {
+            PracticeItem::Stage => {
+                self.stage = if forward {
+                    self.stage % MAX_STAGE + 1
+                } else {
+                    (self.stage + MAX_STAGE - 2) % MAX_STAGE + 1
+                };
+            }
+            PracticeItem::InfiniteLife => self.infinite_life = !self.infinite_life,
+            PracticeItem::Ufo => self.ufo = !self.ufo,
+            PracticeItem::RollingShot => self.shot_types[0] = !self.shot_types[0],
+            PracticeItem::PlungerShot => self.shot_types[1] = !self.shot_types[1],
+            PracticeItem::SquigglyShot => self.shot_types[2] = !self.shot_types[2],
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::canvas;
+
+// ステージ2から9までのリファレンスエイリアンの初期位置(画面下端からの距離)
+const TABLE_INIT_POS_Y: [i32; 8] = [88, 72, 64, 64, 64, 56, 56, 56];
+// ステージ1のリファレンスエイリアンの初期位置(画面下端からの距離)
+const FIRST_INIT_POS_Y: i32 = 112;
+
+// 2周目以降の難易度上昇の設定(ENDLESS時のみ)
+// 1周ごとに初期位置を下げるドット数
+const ENDLESS_DROP_PER_LOOP: i32 = 8;
+// 初期位置の下限(画面下端からの距離、シールドのすぐ上)
+const ENDLESS_MIN_POS_Y: i32 = 56;
+// エイリアンの横方向の移動量の増加間隔(周)と上限
+const ENDLESS_MARCH_LOOP: usize = 2;
+const ENDLESS_MAX_MARCH_DX: i32 = 4;
+// 1周ごとに短くする弾の発射間隔と下限
+const ENDLESS_RELOAD_PER_LOOP: i32 = 2;
+const ENDLESS_MIN_RELOAD_CNT: i32 = 8;
+// エイリアンの弾の移動速度の上限
+const ENDLESS_MAX_BULLET_SPEED: i32 = 4;
+
+// ステージが進んだ時の難易度の上がり方
+#[derive(Clone, Copy, PartialEq)]
+pub enum Progression {
+    // アーケード版と同じく初期位置はステージ2から8ステージごとに繰り返し、それ以外は変化しない
+    Classic,
+    // 2周目以降も周回数に応じて難易度が上がり続ける
+    Endless,
+}
+
+// ステージ開始時に決まる難易度
+pub struct StageParams {
+    // リファレンスエイリアンの初期位置のy座標
+    pub start_pos_y: i32,
+    // エイリアンの横方向の移動量
+    pub march_dx: i32,
+    // 獲得点数で決まる弾の発射間隔から差し引くカウント
+    pub reload_reduction: i32,
+    // 発射間隔の下限
+    pub min_reload_cnt: i32,
+    // エイリアンの弾の移動速度(移動量)
+    pub bullet_speed: i32,
+}
+
+impl Progression {
+    // 設定の保存に使う名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Progression::Classic => "CLASSIC",
+            Progression::Endless => "ENDLESS",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "CLASSIC" => Some(Progression::Classic),
+            "ENDLESS" => Some(Progression::Endless),
+            _ => None,
+        }
+    }
+    // ステージ数(1から)に応じた難易度を返す
+    pub fn params(self, stage: usize) -> StageParams {
+        // 1周目のステージの初期位置
+        let classic_pos_y = if 1 < stage {
+            TABLE_INIT_POS_Y[(stage - 2) % TABLE_INIT_POS_Y.len()]
+        } else {
+            FIRST_INIT_POS_Y
+        };
+        let classic = StageParams {
+            start_pos_y: canvas::GAME_HEIGHT - classic_pos_y,
+            march_dx: 2,
+            reload_reduction: 0,
+            min_reload_cnt: 0,
+            bullet_speed: 1,
+        };
+        // 初期位置の表を何周したか(ステージ1と2から9までが1周目)
+        let lap = if 1 < stage {
+            (stage - 2) / TABLE_INIT_POS_Y.len()
+        } else {
+            0
+        };
+        match self {
+            Progression::Classic => classic,
+            Progression::Endless => {
+                let pos_y =
+                    (classic_pos_y - ENDLESS_DROP_PER_LOOP * lap as i32).max(ENDLESS_MIN_POS_Y);
+                StageParams {
+                    start_pos_y: canvas::GAME_HEIGHT - pos_y,
+                    march_dx: (classic.march_dx + (lap / ENDLESS_MARCH_LOOP) as i32)
+                        .min(ENDLESS_MAX_MARCH_DX),
+                    reload_reduction: ENDLESS_RELOAD_PER_LOOP * lap as i32,
+                    min_reload_cnt: ENDLESS_MIN_RELOAD_CNT,
+                    bullet_speed: (classic.bullet_speed + lap as i32).min(ENDLESS_MAX_BULLET_SPEED),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 画面下端からのリファレンスエイリアンの初期位置
+    fn pos_y(progression: Progression, stage: usize) -> i32 {
+        canvas::GAME_HEIGHT - progression.params(stage).start_pos_y
+    }
+
+    #[test]
+    fn classic_table_repeats_every_eight_stages() {
+        let classic = Progression::Classic;
+        assert_eq!(pos_y(classic, 1), 112);
+        assert_eq!(pos_y(classic, 2), 88);
+        assert_eq!(pos_y(classic, 9), 56);
+        assert_eq!(pos_y(classic, 10), 88);
+        assert_eq!(pos_y(classic, 17), 56);
+        assert_eq!(pos_y(classic, 18), 88);
+        assert_eq!(pos_y(classic, 19), 72);
+        for stage in [1, 2, 9, 10, 17, 18, 19] {
+            let params = classic.params(stage);
+            assert_eq!(params.march_dx, 2);
+            assert_eq!(params.reload_reduction, 0);
+            assert_eq!(params.bullet_speed, 1);
+        }
+    }
+
+    #[test]
+    fn endless_laps_follow_the_table() {
+        let endless = Progression::Endless;
+        // (ステージ数, 初期位置, 横方向の移動量, 発射間隔から差し引くカウント, 弾の速度)
+        let expected = [
+            (1, 112, 2, 0, 1),
+            (2, 88, 2, 0, 1),
+            (9, 56, 2, 0, 1),
+            (10, 80, 2, 2, 2),
+            (17, 56, 2, 2, 2),
+            (18, 72, 3, 4, 3),
+            (19, 56, 3, 4, 3),
+        ];
+        for (stage, pos, march_dx, reload_reduction, bullet_speed) in expected {
+            let params = endless.params(stage);
+            assert_eq!(pos_y(endless, stage), pos, "stage {}", stage);
+            assert_eq!(params.march_dx, march_dx, "stage {}", stage);
+            assert_eq!(params.reload_reduction, reload_reduction, "stage {}", stage);
+            assert_eq!(params.bullet_speed, bullet_speed, "stage {}", stage);
+            assert_eq!(params.min_reload_cnt, ENDLESS_MIN_RELOAD_CNT);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// エイリアンの弾とUFOの動きのルール
+#[derive(Clone, Copy, PartialEq)]
+pub enum Ruleset {
+    // これまで通りのルール(ステージの難易度設定が反映される)
+    Standard,
+    // アーケード版のROMの動きを再現するルール
+    Accurate,
+}
+
+// 以下はAccurateで使うアーケード版のROMの値(READMEの参考文献のComputer Archeologyの解析による)
+// エイリアンの弾はrolling、plunger、squigglyの順に1フレームに1つずつ処理されるので、各弾は3フレームに1回移動する
+pub const SHOT_TURN_NUM: usize = 3;
+// 弾の移動量(ドット数)
+const SHOT_DELTA: i32 = 4;
+// エイリアンの数がこの数より少なくなると弾の移動量が5ドットになる
+const FAST_SHOT_ALIEN_NUM: i32 = 9;
+const FAST_SHOT_DELTA: i32 = 5;
+// 発射間隔を切り替える得点(AReloadScoreTab: 0x02, 0x10, 0x20, 0x30、得点の上位2桁)
+const RELOAD_SCORES: [i32; 4] = [200, 1000, 2000, 3000];
+// 他の弾が移動した回数で表す発射間隔(ShotReloadRate: 0x30, 0x10, 0x0B, 0x08, 0x07)
+const RELOAD_RATES: [i32; 5] = [0x30, 0x10, 0x0B, 0x08, 0x07];
+// UFOの出現間隔のフレーム数(timeToSaucerの初期値0x0600)
+pub const UFO_INTERVAL: u64 = 0x600;
+
+impl Ruleset {
+    // 設定の保存に使う名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Ruleset::Standard => "STANDARD",
+            Ruleset::Accurate => "ACCURATE",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "STANDARD" => Some(Ruleset::Standard),
+            "ACCURATE" => Some(Ruleset::Accurate),
+            _ => None,
+        }
+    }
+}
+
+// Accurateでのエイリアンの弾の移動量(3フレームに1回の移動量)
+pub fn shot_delta(alien_num: i32) -> i32 {
+    if alien_num < FAST_SHOT_ALIEN_NUM {
+        FAST_SHOT_DELTA
+    } else {
+        SHOT_DELTA
+    }
+}
+
+// Accurateでの発射間隔(他の弾がこの回数以上移動していれば次の弾を撃てる)
+pub fn reload_rate(score: i32) -> i32 {
+    let i = RELOAD_SCORES
+        .iter()
+        .take_while(|threshold| **threshold <= score)
+        .count();
+    RELOAD_RATES[i]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_rate_follows_shot_reload_rate_table() {
+        // AReloadScoreTabの各得点の前後でShotReloadRateの値が切り替わる
+        let cases = [
+            (0, 0x30),
+            (199, 0x30),
+            (200, 0x10),
+            (999, 0x10),
+            (1000, 0x0B),
+            (1999, 0x0B),
+            (2000, 0x08),
+            (2999, 0x08),
+            (3000, 0x07),
+            (9990, 0x07),
+        ];
+        for (score, rate) in cases {
+            assert_eq!(reload_rate(score), rate, "score={}", score);
+        }
+    }
+
+    #[test]
+    fn shot_delta_speeds_up_below_nine_aliens() {
+        assert_eq!(shot_delta(55), 4);
+        assert_eq!(shot_delta(9), 4);
+        assert_eq!(shot_delta(8), 5);
+        assert_eq!(shot_delta(1), 5);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::canvas;
+use crate::dot_map::DotMap;
+use crate::math::Vec2;
+
+// シールドが置かれる一番上の行(文字単位)
+pub const SHIELD_ROW: usize = 20;
+// シールドの高さ(文字単位)
+const SHIELD_ROW_NUM: usize = 2;
+// エイリアン1匹分の横幅(ドット単位)
+const ALIEN_CELL_WIDTH: i32 = 16;
+
+// 引数のx座標にシールドを配置する
+// spriteは上半分、下半分の順に並んだシールドのスプライト
+pub fn place(dot_map: &mut DotMap, xs: &[usize], sprite: &[u8]) {
+    let shield_width = sprite.len() / 2;
+    for x in xs {
+        dot_map.map[SHIELD_ROW][*x..x + shield_width].copy_from_slice(&sprite[..shield_width]);
+        dot_map.map[SHIELD_ROW + 1][*x..x + shield_width].copy_from_slice(&sprite[shield_width..]);
+    }
+}
+
+// 削られたシールドを含む行を保存する
+pub fn save_rows(dot_map: &DotMap) -> Vec<Vec<u8>> {
+    dot_map.map[SHIELD_ROW..SHIELD_ROW + SHIELD_ROW_NUM].to_vec()
+}
+
+// 保存したシールドの行を戻す
+pub fn restore_rows(dot_map: &mut DotMap, rows: Vec<Vec<u8>>) {
+    for (i, row) in rows.into_iter().enumerate() {
+        dot_map.map[SHIELD_ROW + i] = row;
+    }
+}
+
+// 引数の文字単位の行がシールドの置かれる行ならば真
+pub fn is_shield_row(char_y: usize) -> bool {
+    (SHIELD_ROW..SHIELD_ROW + SHIELD_ROW_NUM).contains(&char_y)
+}
+
+// 引数の文字単位の行がシールドより下ならば真
+pub fn is_below_shield(char_y: usize) -> bool {
+    SHIELD_ROW + SHIELD_ROW_NUM <= char_y
+}
+
+// 移動したエイリアンの左上座標を引数として、シールドと重なったらエイリアン1匹分のシールドを削り、
+// シールドより下まで侵攻したらすべて消す
+pub fn alien_moved(dot_map: &mut DotMap, alien_pos: Vec2) {
+    if is_below_shield((alien_pos.y / 8) as usize) {
+        remove_all(dot_map);
+    } else {
+        erode(dot_map, alien_pos);
+    }
+}
+
+// エイリアンの左上座標を引数として、そのエイリアン1匹分(16x8ドット)と重なるシールドのドットを消す
+// エイリアンの座標はバイト境界に揃っているため、1行分のみ消せばよい
+pub fn erode(dot_map: &mut DotMap, alien_pos: Vec2) {
+    let char_y = (alien_pos.y / 8) as usize;
+    if !is_shield_row(char_y) {
+        return;
+    }
+    let start = alien_pos.x.max(0);
+    let end = (alien_pos.x + ALIEN_CELL_WIDTH).min(canvas::GAME_WIDTH);
+    for x in start..end {
+        dot_map.map[char_y][x as usize] = 0;
+    }
+}
+
+// エイリアンがシールドより下まで侵攻した時に、すべてのシールドを消す
+pub fn remove_all(dot_map: &mut DotMap) {
+    for row in dot_map.map[SHIELD_ROW..SHIELD_ROW + SHIELD_ROW_NUM].iter_mut() {
+        row.iter_mut().for_each(|dot| *dot = 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // すべてのドットが立ったシールドの行と、その上下の行を持つドットマップ
+    fn filled_map() -> DotMap {
+        let mut dot_map = DotMap::new();
+        for row in dot_map.map[SHIELD_ROW - 1..SHIELD_ROW + SHIELD_ROW_NUM + 1].iter_mut() {
+            row.iter_mut().for_each(|dot| *dot = 0xff);
+        }
+        dot_map
+    }
+
+    #[test]
+    fn erode_clears_only_alien_footprint() {
+        let mut dot_map = filled_map();
+        let alien_pos = Vec2::new(40, (SHIELD_ROW * 8 + 8) as i32);
+        alien_moved(&mut dot_map, alien_pos);
+        for (y, row) in dot_map.map.iter().enumerate() {
+            for (x, dot) in row.iter().enumerate() {
+                let in_footprint = y == SHIELD_ROW + 1 && (40..56).contains(&x);
+                let filled = (SHIELD_ROW - 1..=SHIELD_ROW + SHIELD_ROW_NUM).contains(&y);
+                let expected = if filled && !in_footprint { 0xff } else { 0 };
+                assert_eq!(*dot, expected, "x={} y={}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn erode_clips_at_screen_edge() {
+        let mut dot_map = filled_map();
+        let x = canvas::GAME_WIDTH - 8;
+        erode(&mut dot_map, Vec2::new(x, (SHIELD_ROW * 8) as i32));
+        let row = &dot_map.map[SHIELD_ROW];
+        assert!(row[x as usize..].iter().all(|dot| *dot == 0));
+        assert!(row[..x as usize].iter().all(|dot| *dot == 0xff));
+    }
+
+    #[test]
+    fn erode_ignores_rows_outside_shield() {
+        let mut dot_map = filled_map();
+        let before = dot_map.map.clone();
+        erode(&mut dot_map, Vec2::new(40, ((SHIELD_ROW - 1) * 8) as i32));
+        assert_eq!(dot_map.map, before);
+    }
+
+    #[test]
+    fn remove_all_at_invasion_height() {
+        // シールドの最下行まではシールドを削るだけ
+        let mut dot_map = filled_map();
+        let last_row = (SHIELD_ROW + SHIELD_ROW_NUM - 1) * 8;
+        alien_moved(&mut dot_map, Vec2::new(0, last_row as i32));
+        assert!(dot_map.map[SHIELD_ROW].iter().all(|dot| *dot == 0xff));
+
+        // シールドより下まで侵攻したらシールドの行だけをすべて消す
+        let mut dot_map = filled_map();
+        let below = (SHIELD_ROW + SHIELD_ROW_NUM) * 8;
+        alien_moved(&mut dot_map, Vec2::new(0, below as i32));
+        for y in SHIELD_ROW..SHIELD_ROW + SHIELD_ROW_NUM {
+            assert!(dot_map.map[y].iter().all(|dot| *dot == 0));
+        }
+        assert!(dot_map.map[SHIELD_ROW - 1].iter().all(|dot| *dot == 0xff));
+        assert!(dot_map.map[SHIELD_ROW + SHIELD_ROW_NUM]
+            .iter()
+            .all(|dot| *dot == 0xff));
+    }
+}
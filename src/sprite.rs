@@ -316,6 +316,7 @@ pub fn ret_dot_data(name: &str) -> DotShape {
     }
 }
 // 文字サイズ(8bit x 8bit)のドットマップを返す
+// 数字0～9、英大文字A～Z、記号の順に並ぶ(インデックス番号はchar2indexで求める)
 pub fn char_dot_data() -> Vec<DotShape> {
     let mut num = Vec::new();
     // 0
@@ -468,11 +469,611 @@ pub fn char_dot_data() -> Vec<DotShape> {
             "_ # # # _ _ _ _",
         ]),
     });
+    // A
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // B
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # _ _ _",
+        ]),
+    });
+    // C
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # # # _ _ _",
+        ]),
+    });
+    // D
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # _ _ _",
+        ]),
+    });
+    // E
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # # # # # _ _",
+        ]),
+    });
+    // F
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+        ]),
+    });
+    // G
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # # _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ # # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # # # # _ _",
+        ]),
+    });
+    // H
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // I
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # # # _ _ _",
+        ]),
+    });
+    // J
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # # # _ _ _",
+        ]),
+    });
+    // K
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ # _ _ _",
+            "_ # _ # _ _ _ _",
+            "_ # # _ _ _ _ _",
+            "_ # _ # _ _ _ _",
+            "_ # _ _ # _ _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // L
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # # # # # _ _",
+        ]),
+    });
+    // M
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # _ # # _ _",
+            "_ # _ # _ # _ _",
+            "_ # _ # _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // N
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # _ _ # _ _",
+            "_ # _ # _ # _ _",
+            "_ # _ _ # # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // O
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # # # _ _ _",
+        ]),
+    });
+    // P
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+        ]),
+    });
+    // Q
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ # _ # _ _",
+            "_ # _ _ # _ _ _",
+            "_ _ # # _ # _ _",
+        ]),
+    });
+    // R
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # # # # _ _ _",
+            "_ # _ # _ _ _ _",
+            "_ # _ _ # _ _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // S
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ _ _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # # # _ _ _",
+        ]),
+    });
+    // T
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+        ]),
+    });
+    // U
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # # # _ _ _",
+        ]),
+    });
+    // V
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+        ]),
+    });
+    // W
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ # _ # _ _",
+            "_ # _ # _ # _ _",
+            "_ # # _ # # _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // X
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+        ]),
+    });
+    // Y
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ # _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+        ]),
+    });
+    // Z
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ # # # # # _ _",
+        ]),
+    });
+    // <
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ _ # _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ # _ _ _",
+        ]),
+    });
+    // >
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ _ _ _ _",
+        ]),
+    });
+    // -
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
+    // =
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ # # # # # _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
+    // *
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ # _ # _ # _ _",
+            "_ _ # # # _ _ _",
+            "_ # # # # # _ _",
+            "_ _ # # # _ _ _",
+            "_ # _ # _ # _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
+    // ?
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ # # # _ _ _",
+            "_ # _ _ _ # _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+        ]),
+    });
+    // !
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+        ]),
+    });
+    // .
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+        ]),
+    });
+    // :
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
+    // /
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ # _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ _ _ _ _",
+            "_ # _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
+    // %
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ # # _ _ _ _ _",
+            "_ # # _ _ # _ _",
+            "_ _ _ _ # _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ # _ _ _ _ _",
+            "_ # _ _ # # _ _",
+            "_ _ _ _ # # _ _",
+        ]),
+    });
+    // +
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ # # # # # _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ # _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
+    // 空白
+    num.push(DotShape {
+        width: 8,
+        height: 8,
+        dot_map: convert_dot_map(vec![
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+            "_ _ _ _ _ _ _ _",
+        ]),
+    });
     num
 }
 
+// 文字に対応するchar_dot_dataのインデックス番号を返す
+pub fn char2index(c: char) -> usize {
+    const SYMBOLS: &str = "<>-=*?!.:/%+ ";
+    match c {
+        '0'..='9' => c as usize - '0' as usize,
+        'A'..='Z' => 10 + c as usize - 'A' as usize,
+        'a'..='z' => 10 + c as usize - 'a' as usize,
+        _ => match SYMBOLS.find(c) {
+            Some(i) => 36 + i,
+            // 未対応の文字は空白にする
+            None => 36 + SYMBOLS.len() - 1,
+        },
+    }
+}
+
 // 描画部分を真、非描画部分を偽とするドットマップを返す
-fn convert_dot_map(dot_map: Vec<&str>) -> Vec<Vec<bool>> {
+pub fn convert_dot_map(dot_map: Vec<&str>) -> Vec<Vec<bool>> {
     let mut bool_map = Vec::new();
     for line in dot_map {
         let mut bool_line = Vec::new();
@@ -0,0 +1,222 @@
+use crate::array_sprite::array_text;
+use crate::dot_map::DotMap;
+use crate::game_mode::format_time;
+use crate::math::Vec2;
+use crate::player::ShotResult;
+
+// セッション履歴に残すゲーム数
+const HISTORY_NUM: usize = 8;
+// エイリアンの種類の表示名(0: タコ, 1: カニ, 2: イカ)
+const ALIEN_NAMES: [&str; 3] = ["OCTOPUS", "CRAB", "SQUID"];
+
+// 1ステージ(ゲーム全体の集計にも使う)の成績
+#[derive(Clone, Default)]
+pub struct StageStats {
+    pub shots: i32,
+    // エイリアンとUFOに当たった弾の数
+    pub hits: i32,
+    pub ufos: i32,
+    pub ticks: i32,
+    pub lives_lost: i32,
+    // エイリアンの種類ごとの獲得点数
+    pub alien_points: [i32; 3],
+    pub ufo_points: i32,
+    // モダンの得点方式で加えた倍率やボーナスの点数
+    pub bonus_points: i32,
+}
+
+impl StageStats {
+    // プレイヤーの弾が何かに当たるか画面上部に届いた時に呼び出す
+    pub fn record_shot(&mut self, result: ShotResult) {
+        match result {
+            ShotResult::Alien {
+                alien_type, score, ..
+            } => {
+                self.hits += 1;
+                self.alien_points[alien_type] += score;
+            }
+            ShotResult::Ufo { score } => {
+                self.hits += 1;
+                self.ufos += 1;
+                self.ufo_points += score;
+            }
+            ShotResult::Obstacle | ShotResult::Top => (),
+        }
+    }
+    // 命中率(%)、1発も撃っていなければNone
+    pub fn accuracy(&self) -> Option<i32> {
+        if self.shots <= 0 {
+            None
+        } else {
+            Some(self.hits * 100 / self.shots)
+        }
+    }
+    pub fn add(&mut self, other: &StageStats) {
+        self.shots += other.shots;
+        self.hits += other.hits;
+        self.ufos += other.ufos;
+        self.ticks += other.ticks;
+        self.lives_lost += other.lives_lost;
+        for (points, other_points) in self.alien_points.iter_mut().zip(other.alien_points) {
+            *points += other_points;
+        }
+        self.ufo_points += other.ufo_points;
+        self.bonus_points += other.bonus_points;
+    }
+    // 複数のステージの成績を合計する
+    pub fn total(stages: &[StageStats]) -> StageStats {
+        let mut total = StageStats::default();
+        for stats in stages {
+            total.add(stats);
+        }
+        total
+    }
+    // 成績画面に表示する行
+    pub fn lines(&self) -> Vec<String> {
+        let accuracy = match self.accuracy() {
+            Some(accuracy) => format!("{}%", accuracy),
+            None => "-".to_string(),
+        };
+        let mut lines = vec![
+            line("SHOTS", &self.shots.to_string()),
+            line("HITS", &self.hits.to_string()),
+            line("ACCURACY", &accuracy),
+            line("UFO", &self.ufos.to_string()),
+            line("TIME", &format_time(self.ticks)),
+            line("LIVES LOST", &self.lives_lost.to_string()),
+        ];
+        for (name, points) in ALIEN_NAMES.iter().zip(self.alien_points).rev() {
+            lines.push(line(name, &points.to_string()));
+        }
+        lines.push(line("UFO POINTS", &self.ufo_points.to_string()));
+        lines.push(line("BONUS", &self.bonus_points.to_string()));
+        lines
+    }
+}
+
+// 1ゲーム分の記録
+pub struct GameRecord {
+    // 遊んだルールの名前
+    pub mode: String,
+    pub score: i32,
+    // 到達したステージ数
+    pub stage: usize,
+    pub total: StageStats,
+}
+
+// このセッション(ページを開いてから)で遊んだゲームの記録
+#[derive(Default)]
+pub struct SessionHistory {
+    // 新しい順
+    pub games: Vec<GameRecord>,
+}
+
+impl SessionHistory {
+    pub fn push(&mut self, record: GameRecord) {
+        self.games.insert(0, record);
+        self.games.truncate(HISTORY_NUM);
+    }
+    // 履歴の画面を描く
+    pub fn draw(&self, dot_map: &mut DotMap, char_list: &[Vec<u8>]) {
+        let mut lines = vec!["MODE         SCORE STG ACC".to_string()];
+        for game in self.games.iter() {
+            let accuracy = match game.total.accuracy() {
+                Some(accuracy) => format!("{:>3}", accuracy),
+                None => "  -".to_string(),
+            };
+            lines.push(format!(
+                "{:<12} {:>5} {:>3} {}",
+                game.mode,
+                game.score,
+                game.stage.min(999),
+                accuracy
+            ));
+        }
+        draw_lines(dot_map, char_list, "SESSION HISTORY", &lines);
+    }
+}
+
+// 見出しを中央に、各行を左詰めで16ドットおきに描く
+pub fn draw_lines(dot_map: &mut DotMap, char_list: &[Vec<u8>], title: &str, lines: &[String]) {
+    let title_x = (dot_map.map[0].len() as i32 - 8 * title.len() as i32) / 2;
+    array_text(&mut dot_map.map, Vec2::new(title_x, 8), title, char_list);
+    for (i, text) in lines.iter().enumerate() {
+        let pos = Vec2::new(0, 24 + 16 * i as i32);
+        array_text(&mut dot_map.map, pos, text, char_list);
+    }
+}
+
+// 項目名と値を1行にまとめる(値は右詰め)
+fn line(label: &str, value: &str) -> String {
+    format!("  {:<12}{:>10}", label, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_shot_counts_hits_and_points() {
+        let mut stats = StageStats::default();
+        stats.record_shot(ShotResult::Alien {
+            index: 0,
+            alien_type: 2,
+            score: 30,
+        });
+        stats.record_shot(ShotResult::Alien {
+            index: 1,
+            alien_type: 0,
+            score: 10,
+        });
+        stats.record_shot(ShotResult::Ufo { score: 150 });
+        // 外れた弾は命中数に数えない
+        stats.record_shot(ShotResult::Obstacle);
+        stats.record_shot(ShotResult::Top);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.ufos, 1);
+        assert_eq!(stats.alien_points, [10, 0, 30]);
+        assert_eq!(stats.ufo_points, 150);
+        // 撃った数はGame::updateで数える
+        assert_eq!(stats.shots, 0);
+    }
+
+    #[test]
+    fn accuracy_rounds_down_and_handles_no_shots() {
+        let mut stats = StageStats::default();
+        assert_eq!(stats.accuracy(), None);
+        stats.shots = 3;
+        assert_eq!(stats.accuracy(), Some(0));
+        stats.hits = 2;
+        assert_eq!(stats.accuracy(), Some(66));
+        stats.hits = 3;
+        assert_eq!(stats.accuracy(), Some(100));
+        assert!(stats.lines().contains(&line("ACCURACY", "100%")));
+        assert!(StageStats::default()
+            .lines()
+            .contains(&line("ACCURACY", "-")));
+    }
+
+    #[test]
+    fn total_adds_every_field() {
+        let stage = StageStats {
+            shots: 10,
+            hits: 4,
+            ufos: 1,
+            ticks: 600,
+            lives_lost: 1,
+            alien_points: [10, 20, 30],
+            ufo_points: 100,
+            bonus_points: 5,
+        };
+        let total = StageStats::total(&[stage.clone(), stage]);
+        assert_eq!(total.shots, 20);
+        assert_eq!(total.hits, 8);
+        assert_eq!(total.ufos, 2);
+        assert_eq!(total.ticks, 1200);
+        assert_eq!(total.lives_lost, 2);
+        assert_eq!(total.alien_points, [20, 40, 60]);
+        assert_eq!(total.ufo_points, 200);
+        assert_eq!(total.bonus_points, 10);
+        assert_eq!(StageStats::total(&[]).shots, 0);
+    }
+}
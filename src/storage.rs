@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use web_sys::window;
+
+// キーに対応させて文字列を保存する保存先
+pub trait Storage {
+    // 保存されていなければNoneを返す
+    fn load(&self, key: &str) -> Option<String>;
+    fn save(&mut self, key: &str, value: &str);
+}
+
+// ブラウザのlocalStorageに保存する
+pub struct LocalStorage;
+
+impl LocalStorage {
+    // localStorageが利用できなければNoneを返す
+    pub fn new() -> Option<Self> {
+        window()?.local_storage().ok()??;
+        Some(LocalStorage)
+    }
+}
+
+impl Storage for LocalStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        let storage = window()?.local_storage().ok()??;
+        storage.get_item(key).ok()?
+    }
+    fn save(&mut self, key: &str, value: &str) {
+        let storage = window().and_then(|w| w.local_storage().ok().flatten());
+        match storage {
+            Some(storage) => {
+                if let Err(err) = storage.set_item(key, value) {
+                    log::info!("{}の保存に失敗しました。 {:#?}", key, err);
+                }
+            }
+            None => log::info!("localStorageが利用できません。"),
+        }
+    }
+}
+
+// メモリ上に保存する(ブラウザ以外で動かす場合用)
+#[derive(Default)]
+pub struct MemoryStorage {
+    data: HashMap<String, String>,
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        self.data.get(key).cloned()
+    }
+    fn save(&mut self, key: &str, value: &str) {
+        self.data.insert(key.to_string(), value.to_string());
+    }
+}
@@ -1,7 +1,10 @@
-use crate::array_sprite::array_sprite;
+use crate::array_sprite::{array_sprite, array_text};
 use crate::canvas;
 use crate::math::Vec2;
 
+// 得点の下の段(y=24)
+const STATUS_ROW: usize = 3;
+
 pub struct TopArea {
     pub top: Vec<Vec<u8>>,
     num_sprite: Vec<Vec<u8>>,
@@ -19,17 +22,116 @@ impl TopArea {
     pub fn all_clear(&mut self) {
         self.top = vec![vec![0; canvas::TOP_WIDTH as usize]; (canvas::TOP_HEIGHT / 8) as usize];
     }
-    // 上に獲得得点を表示
-    pub fn draw_score(&mut self, mut score: i32) {
+    // 上に各プレイヤーの獲得得点とハイスコアを表示
+    // hide_activeが真の場合、操作中のプレイヤーの得点を消す(点滅表示用)
+    pub fn draw_score(
+        &mut self,
+        scores: [i32; 2],
+        hi_score: i32,
+        active: usize,
+        hide_active: bool,
+    ) {
+        array_text(&mut self.top, Vec2::new(8, 0), "SCORE<1>", &self.num_sprite);
+        array_text(
+            &mut self.top,
+            Vec2::new(80, 0),
+            "HI-SCORE",
+            &self.num_sprite,
+        );
+        array_text(
+            &mut self.top,
+            Vec2::new(152, 0),
+            "SCORE<2>",
+            &self.num_sprite,
+        );
+        self.draw_num(Vec2::new(96, 16), hi_score);
+        for (i, score) in scores.iter().enumerate() {
+            let pos = Vec2::new(24 + 144 * i as i32, 16);
+            if i == active && hide_active {
+                array_text(&mut self.top, pos, "     ", &self.num_sprite);
+            } else {
+                self.draw_num(pos, *score);
+            }
+        }
+    }
+    // 得点の下の段(ステージ数、倍率、タイマー、実績の表示)を消す
+    // 実績の表示は段全体に書くので、各項目を描き直す前に消して消え残りを防ぐ
+    pub fn clear_status_row(&mut self) {
+        self.top[STATUS_ROW].fill(0);
+    }
+    // 得点の下にステージ数を表示(Noneの場合は消す)
+    pub fn draw_stage(&mut self, stage: Option<usize>) {
+        let text = match stage {
+            Some(stage) => format!("STAGE{:>3}", stage.min(999)),
+            None => "        ".to_string(),
+        };
+        array_text(&mut self.top, Vec2::new(80, 24), &text, &self.num_sprite);
+    }
+    // 左下に得点の倍率を表示(Noneの場合は消す)
+    pub fn draw_combo(&mut self, multiplier: Option<i32>) {
+        let text = match multiplier {
+            Some(multiplier) => format!("COMBO X{}", multiplier),
+            None => "        ".to_string(),
+        };
+        array_text(&mut self.top, Vec2::new(8, 24), &text, &self.num_sprite);
+    }
+    // 右下にタイマーを表示(Noneの場合は消す)
+    pub fn draw_timer(&mut self, text: Option<String>) {
+        let text = text.unwrap_or_default();
+        array_text(
+            &mut self.top,
+            Vec2::new(152, 24),
+            &format!("{:>9}", text),
+            &self.num_sprite,
+        );
+    }
+    // 実績の解除を知らせる表示を画面上部の最下段に中央寄せで上書きする
+    pub fn draw_toast(&mut self, text: &str) {
+        array_text(
+            &mut self.top,
+            Vec2::new(0, 24),
+            &format!("{:^28}", text),
+            &self.num_sprite,
+        );
+    }
+    // 5桁の数値を表示
+    fn draw_num(&mut self, mut pos: Vec2, mut score: i32) {
         let mut score_num = Vec::new();
         for _ in 0..5 {
             score_num.push(score % 10);
             score /= 10;
         }
-        let mut pos = Vec2::new(24, 24);
         for i in (0..5).rev() {
             array_sprite(&mut self.top, pos, &self.num_sprite[score_num[i] as usize]);
             pos.x += 8;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite;
+
+    fn top_area() -> TopArea {
+        let char_list = sprite::char_dot_data()
+            .iter()
+            .map(|c| c.create_dot_map())
+            .collect();
+        TopArea::new(char_list)
+    }
+
+    #[test]
+    fn toast_does_not_remain_after_expiry() {
+        let mut top = top_area();
+        top.draw_toast("UNLOCKED: NO MISS");
+        // 実績の表示が終わった後のフレームの描画
+        top.clear_status_row();
+        top.draw_stage(Some(1));
+        top.draw_combo(None);
+        top.draw_timer(None);
+        let mut expected = top_area();
+        expected.draw_stage(Some(1));
+        assert_eq!(top.top, expected.top);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::input::KeyDown;
+use web_sys::window;
+
+// 画面上のタッチ操作のボタン
+#[derive(Clone, Copy, PartialEq)]
+pub enum TouchButton {
+    Left,
+    Right,
+    // メニューのカーソルを上下に移動させる
+    Up,
+    Down,
+    Fire,
+    Pause,
+}
+
+impl TouchButton {
+    pub const ALL: [TouchButton; 6] = [
+        TouchButton::Left,
+        TouchButton::Right,
+        TouchButton::Up,
+        TouchButton::Down,
+        TouchButton::Fire,
+        TouchButton::Pause,
+    ];
+
+    // ボタンに表示する文字
+    pub fn label(self) -> &'static str {
+        match self {
+            TouchButton::Left => "◀",
+            TouchButton::Right => "▶",
+            TouchButton::Up => "▲",
+            TouchButton::Down => "▼",
+            TouchButton::Fire => "FIRE",
+            TouchButton::Pause => "PAUSE",
+        }
+    }
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|b| *b == self).unwrap()
+    }
+}
+
+// タッチ操作のボタンの押下状態
+// ボタンごとにtouchstart、touchendを受け取るので、移動しながら撃つような複数の指での操作もできる
+#[derive(Default)]
+pub struct TouchInput {
+    // タッチ操作が使える端末ならば真(ボタンを表示する)
+    pub enabled: bool,
+    pressed: [bool; TouchButton::ALL.len()],
+}
+
+impl TouchInput {
+    pub fn new() -> Self {
+        TouchInput {
+            enabled: is_touch_device(),
+            ..Self::default()
+        }
+    }
+    pub fn set(&mut self, button: TouchButton, pressed: bool) {
+        self.pressed[button.index()] = pressed;
+    }
+    pub fn release_all(&mut self) {
+        self.pressed = Default::default();
+    }
+    // 押されているボタンの入力をinput_keyに加える
+    pub fn apply(&self, input_key: &mut KeyDown) {
+        input_key.left |= self.pressed[TouchButton::Left.index()];
+        input_key.right |= self.pressed[TouchButton::Right.index()];
+        input_key.up |= self.pressed[TouchButton::Up.index()];
+        input_key.down |= self.pressed[TouchButton::Down.index()];
+        input_key.shot |= self.pressed[TouchButton::Fire.index()];
+        input_key.pause |= self.pressed[TouchButton::Pause.index()];
+    }
+}
+
+// タッチ操作ができる端末ならば真
+fn is_touch_device() -> bool {
+    window().is_some_and(|w| 0 < w.navigator().max_touch_points())
+}
@@ -1,8 +1,10 @@
 use crate::audio::Audio;
 use crate::canvas;
+use crate::config::GameConfig;
+use crate::game_mode::TICKS_PER_SEC;
 use crate::math::Vec2;
+use crate::ruleset::{self, Ruleset};
 use crate::{array_sprite::ArraySprite, dot_map::DotMap};
-use instant::Instant;
 use web_sys::AudioBufferSourceNode;
 
 pub struct Explosion {
@@ -78,14 +80,14 @@ impl ArraySprite for Explosion {
 }
 
 pub struct Ufo {
-    width: i32,
-    pos: Vec2,              // 左上位置
-    pre_pos: Vec2,          // 前回描画時の位置
-    live: bool,             // 存在しているか否か
-    move_dir: i32,          // 移動方向
-    lapse_time: Instant,    // 前回画面から消滅したときの時刻
-    score_table: [i32; 15], // プレイヤーの発射数に対応した獲得得点表
-    sprite: Vec<u8>,        // 左側から縦8ピクセルずつを8bitのベクタで表す
+    pub width: i32,
+    pub pos: Vec2,              // 左上位置
+    pre_pos: Vec2,              // 前回描画時の位置
+    pub live: bool,             // 存在しているか否か
+    pub move_dir: i32,          // 移動方向
+    lapse_cnt: u64,             // 前回画面から消滅してからの経過tick数
+    pub score_table: [i32; 15], // プレイヤーの発射数に対応した獲得得点表
+    sprite: Vec<u8>,            // 左側から縦8ピクセルずつを8bitのベクタで表す
     pub explosion: Explosion,
     flying_sound: Option<AudioBufferSourceNode>,
 }
@@ -101,7 +103,7 @@ impl Ufo {
             score_table: [
                 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100, 100,
             ],
-            lapse_time: Instant::now(),
+            lapse_cnt: 0,
             sprite,
             explosion: Explosion {
                 pos: Vec2::new(0, 0),
@@ -115,14 +117,18 @@ impl Ufo {
     }
     pub fn reset(&mut self) {
         self.live = false;
-        self.lapse_time = Instant::now();
+        self.lapse_cnt = 0;
+        // 飛行音のループ再生を止める
+        if let Some(sound_node) = self.flying_sound.take() {
+            sound_node.stop().unwrap();
+        }
     }
     fn remove(&mut self, dot_map: &mut DotMap) {
         self.live = false;
         // 移動方向反転
         self.move_dir *= -1;
         // タイマーリセット
-        self.lapse_time = Instant::now();
+        self.lapse_cnt = 0;
 
         // 前回描画した部分を消す
         self.erase(dot_map, self.pre_pos);
@@ -147,7 +153,15 @@ impl Ufo {
         self.explosion.score = score;
         score
     }
-    pub fn update(&mut self, dot_map: &mut DotMap, fire_cnt: i32, alien_num: i32, audio: &Audio) {
+    pub fn update(
+        &mut self,
+        dot_map: &mut DotMap,
+        fire_cnt: i32,
+        alien_num: i32,
+        squiggly_live: bool,
+        audio: &Audio,
+        config: &GameConfig,
+    ) {
         self.pre_pos = self.pos;
         self.explosion.update_draw(dot_map);
         // 画面の反対側まで到達した場合
@@ -162,7 +176,13 @@ impl Ufo {
             self.pos.x += self.move_dir;
         } else {
             // 消滅してから一定時間経過して、かつエイリアンの数が8以上だったら
-            if 25 < self.lapse_time.elapsed().as_secs() && 7 < alien_num {
+            // Accurateではアーケード版の間隔で、squiggly shotと同じ枠を使うのでその弾が無い時だけ出現する
+            self.lapse_cnt += 1;
+            let (interval, slot_free) = match config.ruleset {
+                Ruleset::Standard => (config.ufo_interval * TICKS_PER_SEC as u64, true),
+                Ruleset::Accurate => (ruleset::UFO_INTERVAL, !squiggly_live),
+            };
+            if self.lapse_cnt > interval && slot_free && 7 < alien_num {
                 // UFOが出現する瞬間
                 self.live = true;
                 // プレイヤーの発射数が偶数であれば右から左へ動く
@@ -177,7 +197,7 @@ impl Ufo {
 
                 // UFO飛行音ループ再生開始
                 if let Some(sound) = &audio.ufo_flying {
-                    self.flying_sound = Some(audio.play_looping_sound(sound));
+                    self.flying_sound = audio.play_looping_sound(sound);
                 }
             }
         }
@@ -204,3 +224,36 @@ impl ArraySprite for Ufo {
         &self.sprite
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::Audio;
+    use crate::config::GameConfig;
+    use crate::game::Game;
+    use crate::ruleset::{self, Ruleset};
+
+    // Accurateで出現間隔が経過した時に、UFOが出現すれば真
+    fn spawns(squiggly_live: bool) -> bool {
+        let config = GameConfig {
+            ruleset: Ruleset::Accurate,
+            ..GameConfig::default()
+        };
+        let mut game = Game::new(config);
+        game.ufo.lapse_cnt = ruleset::UFO_INTERVAL;
+        game.ufo.update(
+            &mut game.map,
+            0,
+            55,
+            squiggly_live,
+            &Audio::silent(),
+            &game.config,
+        );
+        game.ufo.live
+    }
+
+    #[test]
+    fn accurate_no_ufo_while_squiggly_live() {
+        assert!(spawns(false));
+        assert!(!spawns(true));
+    }
+}